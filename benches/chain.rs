@@ -0,0 +1,36 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use http_ip::http::HeaderMapClientIp;
+use http_ip::http::http_ext::HeaderMap;
+use http_ip::http::http_ext::header::FORWARDED;
+use http_ip::forwarded::parse_forwarded_for;
+
+use core::hint::black_box;
+use core::net::IpAddr;
+
+fn collect_vec(headers: &HeaderMap) -> Vec<IpAddr> {
+    headers.get_all(FORWARDED)
+           .into_iter()
+           .filter_map(|header| header.to_str().ok()).flat_map(parse_forwarded_for)
+           .filter_map(|node| node.ip())
+           .collect()
+}
+
+fn chain(c: &mut Criterion) {
+    let mut headers = HeaderMap::new();
+    headers.append(FORWARDED, "For=203.0.113.195;proto=https,For=10.0.0.1".parse().unwrap());
+
+    let mut group = c.benchmark_group("chain_2_hops");
+    group.bench_function("smallvec", |b| b.iter(|| black_box(black_box(&headers).extract_chain_smallvec())));
+    group.bench_function("vec", |b| b.iter(|| black_box(collect_vec(black_box(&headers)))));
+    group.finish();
+
+    headers.append(FORWARDED, "For=10.0.0.2,For=10.0.0.3,For=10.0.0.4,For=10.0.0.5,For=10.0.0.6".parse().unwrap());
+    let mut group = c.benchmark_group("chain_7_hops");
+    group.bench_function("smallvec", |b| b.iter(|| black_box(black_box(&headers).extract_chain_smallvec())));
+    group.bench_function("vec", |b| b.iter(|| black_box(collect_vec(black_box(&headers)))));
+    group.finish();
+}
+
+criterion_group!(benches, chain);
+criterion_main!(benches);
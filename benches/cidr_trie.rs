@@ -0,0 +1,43 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use http_ip::filter::{self, Cidr, CidrTrie, Filter};
+
+use core::hint::black_box;
+use core::net::{IpAddr, Ipv4Addr};
+
+//xorshift to have reproducible input
+fn next_random(state: &mut u32) -> u32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    *state
+}
+
+fn cidr_trie(c: &mut Criterion) {
+    let mut state = 0x1234_5678;
+    let cidrs: Vec<Cidr> = (0..1000).map(|_| {
+        let ip = IpAddr::V4(Ipv4Addr::from_bits(next_random(&mut state)));
+        let prefix = 16 + (next_random(&mut state) % 17) as u8;
+        Cidr::new(ip, prefix).expect("valid prefix")
+    }).collect();
+    let ips: Vec<IpAddr> = (0..64).map(|_| IpAddr::V4(Ipv4Addr::from_bits(next_random(&mut state)))).collect();
+
+    let trie = CidrTrie::from_cidrs(&cidrs);
+    let linear = filter::collection_or(cidrs.as_slice());
+
+    let mut group = c.benchmark_group("cidr_1000");
+    group.bench_function("trie", |b| b.iter(|| {
+        for ip in ips.iter() {
+            black_box(black_box(&trie).is_match(*ip));
+        }
+    }));
+    group.bench_function("linear", |b| b.iter(|| {
+        for ip in ips.iter() {
+            black_box(black_box(&linear).is_match(*ip));
+        }
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, cidr_trie);
+criterion_main!(benches);
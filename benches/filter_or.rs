@@ -0,0 +1,43 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use http_ip::filter::{self, Cidr, Filter};
+
+use core::hint::black_box;
+use core::net::{IpAddr, Ipv4Addr};
+
+//Evaluates filters strictly from left to right, as `Or` did before cost based ordering
+struct LeftToRight<F1, F2>(F1, F2);
+
+impl<F1: Filter, F2: Filter> Filter for LeftToRight<F1, F2> {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.0.is_match(ip) || self.1.is_match(ip)
+    }
+}
+
+const CIDRS: [&str; 8] = ["10.0.0.0/8", "172.16.0.0/12", "192.168.0.0/16", "100.64.0.0/10", "198.18.0.0/15", "169.254.0.0/16", "127.0.0.0/8", "192.0.0.0/24"];
+
+fn filter_or(c: &mut Criterion) {
+    let cidrs = CIDRS.map(|cidr| Cidr::from_text(cidr).expect("valid cidr"));
+    let proxy = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 10));
+    let ips = [proxy, IpAddr::V4(Ipv4Addr::new(198, 51, 100, 1)), IpAddr::V4(Ipv4Addr::new(10, 1, 1, 1))];
+
+    let ordered = filter::or(filter::collection_or(cidrs), proxy);
+    let naive = LeftToRight(filter::collection_or(cidrs), proxy);
+
+    let mut group = c.benchmark_group("mixed_or");
+    group.bench_function("cost_ordered", |b| b.iter(|| {
+        for ip in ips.iter() {
+            black_box(black_box(&ordered).is_match(*ip));
+        }
+    }));
+    group.bench_function("left_to_right", |b| b.iter(|| {
+        for ip in ips.iter() {
+            black_box(black_box(&naive).is_match(*ip));
+        }
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, filter_or);
+criterion_main!(benches);
@@ -0,0 +1,25 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use http_ip::forwarded::{parse_x_forwarded_for, parse_x_forwarded_for_single};
+
+use core::hint::black_box;
+
+fn x_forwarded_for(c: &mut Criterion) {
+    const VALUES: [&str; 2] = ["203.0.113.195", "2001:db8:85a3:8d3:1319:8a2e:370:7348"];
+
+    let mut group = c.benchmark_group("x_forwarded_for_single_ip");
+    group.bench_function("fast_path", |b| b.iter(|| {
+        for value in VALUES {
+            black_box(parse_x_forwarded_for_single(black_box(value)).and_then(|node| node.ip()));
+        }
+    }));
+    group.bench_function("iterator", |b| b.iter(|| {
+        for value in VALUES {
+            black_box(parse_x_forwarded_for(black_box(value)).next().and_then(|node| node.ip()));
+        }
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, x_forwarded_for);
+criterion_main!(benches);
@@ -8,14 +8,17 @@ use core::net::{IpAddr, SocketAddr};
 pub use axum08::*;
 use axum08::extract::FromRequestParts;
 
-use crate::filter::Filter;
+use crate::filter::{Filter, MatchNone};
 use crate::http::HeaderMapClientIp;
 
 #[repr(transparent)]
 #[derive(Copy, Clone)]
 ///ClientIp extractor
 ///
-///Provided `F` parameter can be used to customize filter selection. Use `nil` type to only extract rightmost IP.
+///Provided `F` parameter can be used to customize filter selection.
+///By default it is [MatchNone](../filter/struct.MatchNone.html), which only extracts rightmost IP.
+///
+///When using default filter with custom state, implement `FromRef<MyState>` for `MatchNone`
 ///
 ///Defaults to `axum::extract::ConnectInfo` if corresponding header cannot provide ip
 ///
@@ -53,7 +56,7 @@ use crate::http::HeaderMapClientIp;
 ///};
 ///let app: Router<MyState> = Router::new().route("/users", get(create_user)).with_state(state);
 ///```
-pub struct ClientIp<F: Filter> {
+pub struct ClientIp<F: Filter = MatchNone> {
     ///Underlying IP addr if available
     pub inner: Option<IpAddr>,
     _filter: marker::PhantomData<F>
@@ -82,18 +85,167 @@ impl<F: Filter> fmt::Debug for ClientIp<F> {
     }
 }
 
+async fn extract_client_ip<S: Send + Sync, F: Filter + extract::FromRef<S>>(parts: &mut http::request::Parts, state: &S) -> Option<IpAddr> {
+    let filter: F = extract::FromRef::from_ref(state);
+    if let Some(ip) = parts.headers.extract_filtered_forwarded_ip(&filter) {
+        Some(ip)
+    } else if let Ok(addr) = extract::ConnectInfo::<SocketAddr>::from_request_parts(parts, state).await {
+        Some(addr.ip())
+    } else {
+        None
+    }
+}
+
 impl<S: Send + Sync, F: Send + Sync + Filter + Clone + extract::FromRef<S>> FromRequestParts<S> for ClientIp<F> {
     type Rejection = core::convert::Infallible;
 
     async fn from_request_parts(parts: &mut http::request::Parts, state: &S) -> Result<Self, Self::Rejection> {
-        let filter: F = extract::FromRef::from_ref(state);
-        let ip = if let Some(ip) = parts.headers.extract_filtered_forwarded_ip(&filter) {
-            Some(ip)
-        } else if let Ok(addr) = extract::ConnectInfo::<SocketAddr>::from_request_parts(parts, state).await {
-            Some(addr.ip())
-        } else {
-            None
-        };
+        let ip = extract_client_ip::<S, F>(parts, state).await;
         Ok(ClientIp::new(ip))
     }
 }
+
+#[repr(transparent)]
+#[derive(Copy, Clone)]
+///ClientIp extractor, which only inspects `Forwarded` and `X-Forwarded-For` headers
+///
+///Unlike [ClientIp](struct.ClientIp.html), it never falls back to `axum::extract::ConnectInfo`,
+///hence `None` always indicates that headers provided no IP.
+///This is useful when socket address is meaningless (e.g. behind load balancer that always sets headers)
+pub struct HeaderOnlyClientIp<F: Filter = MatchNone> {
+    ///Underlying IP addr if available
+    pub inner: Option<IpAddr>,
+    _filter: marker::PhantomData<F>
+}
+
+impl<F: Filter> HeaderOnlyClientIp<F> {
+    #[inline(always)]
+    ///Access underlying value
+    pub fn into_inner(self) -> Option<IpAddr> {
+        self.inner
+    }
+}
+
+impl<F: Filter> fmt::Debug for HeaderOnlyClientIp<F> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, fmt)
+    }
+}
+
+impl<S: Send + Sync, F: Send + Sync + Filter + Clone + extract::FromRef<S>> FromRequestParts<S> for HeaderOnlyClientIp<F> {
+    type Rejection = core::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut http::request::Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let filter: F = extract::FromRef::from_ref(state);
+        Ok(Self {
+            inner: parts.headers.extract_filtered_forwarded_ip(&filter),
+            _filter: marker::PhantomData,
+        })
+    }
+}
+
+impl extract::FromRef<()> for MatchNone {
+    #[inline(always)]
+    fn from_ref(_: &()) -> Self {
+        MatchNone
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Rejection of [RequiredClientIp](struct.RequiredClientIp.html) when client's IP cannot be determined
+///
+///Responds with `400 Bad Request` by default
+pub struct ClientIpRejection {
+    status: http::StatusCode,
+}
+
+impl ClientIpRejection {
+    #[inline(always)]
+    ///Creates new instance with `400 Bad Request` status
+    pub const fn new() -> Self {
+        Self {
+            status: http::StatusCode::BAD_REQUEST,
+        }
+    }
+
+    #[inline(always)]
+    ///Sets status code of the response
+    pub const fn with_status(mut self, status: http::StatusCode) -> Self {
+        self.status = status;
+        self
+    }
+
+    #[inline(always)]
+    ///Returns status code of the response
+    pub const fn status(&self) -> http::StatusCode {
+        self.status
+    }
+}
+
+impl Default for ClientIpRejection {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for ClientIpRejection {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("Unable to determine client IP")
+    }
+}
+
+impl response::IntoResponse for ClientIpRejection {
+    #[inline]
+    fn into_response(self) -> response::Response {
+        (self.status, "Unable to determine client IP").into_response()
+    }
+}
+
+#[repr(transparent)]
+#[derive(Copy, Clone)]
+///ClientIp extractor, which rejects request when client's IP cannot be determined
+///
+///Works the same way as [ClientIp](struct.ClientIp.html), but rejects with [ClientIpRejection](struct.ClientIpRejection.html)
+///using status code `STATUS` (defaults to `400 Bad Request`)
+///
+///`STATUS` must be valid status code, otherwise `500 Internal Server Error` is used
+pub struct RequiredClientIp<F: Filter = MatchNone, const STATUS: u16 = 400> {
+    ///Underlying IP addr
+    pub inner: IpAddr,
+    _filter: marker::PhantomData<F>
+}
+
+impl<F: Filter, const STATUS: u16> RequiredClientIp<F, STATUS> {
+    #[inline(always)]
+    ///Access underlying value
+    pub fn into_inner(self) -> IpAddr {
+        self.inner
+    }
+}
+
+impl<F: Filter, const STATUS: u16> fmt::Debug for RequiredClientIp<F, STATUS> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.inner, fmt)
+    }
+}
+
+impl<S: Send + Sync, F: Send + Sync + Filter + Clone + extract::FromRef<S>, const STATUS: u16> FromRequestParts<S> for RequiredClientIp<F, STATUS> {
+    type Rejection = ClientIpRejection;
+
+    async fn from_request_parts(parts: &mut http::request::Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match extract_client_ip::<S, F>(parts, state).await {
+            Some(inner) => Ok(Self {
+                inner,
+                _filter: marker::PhantomData,
+            }),
+            None => {
+                let status = http::StatusCode::from_u16(STATUS).unwrap_or(http::StatusCode::INTERNAL_SERVER_ERROR);
+                Err(ClientIpRejection::new().with_status(status))
+            }
+        }
+    }
+}
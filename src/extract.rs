@@ -0,0 +1,115 @@
+//! Common types describing extraction of client's IP from headers
+
+#[cfg(feature = "alloc")]
+use core::net::IpAddr;
+
+use crate::forwarded::ForwardedNode;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+///Header from which IP information is extracted
+pub enum HeaderSource {
+    ///`Forwarded` header
+    Forwarded,
+    ///`X-Forwarded-For` header
+    XForwardedFor,
+    ///`X-Real-IP` header
+    XRealIp,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+///Classification of proxy chain's hop, used by `resolve_client_ip_by`
+pub enum HopKind {
+    ///Hop is trusted proxy, hence it is skipped
+    Trusted,
+    ///Hop is client, which is returned as result of resolution
+    Client,
+    ///Hop is not acceptable, aborting resolution
+    Reject,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+///Configuration of client IP extraction
+pub struct ExtractConfig<'a> {
+    ///Order in which headers are inspected
+    ///
+    ///First header, present in request and containing at least one node, is used to determine client's IP.
+    ///Remaining headers are ignored in this case.
+    pub header_priority: &'a [HeaderSource],
+}
+
+impl ExtractConfig<'static> {
+    ///Default configuration, preferring `Forwarded` over `X-Forwarded-For`
+    pub const DEFAULT: Self = Self {
+        header_priority: &[HeaderSource::Forwarded, HeaderSource::XForwardedFor],
+    };
+}
+
+impl Default for ExtractConfig<'static> {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+///Single step of IP resolution
+pub struct ResolutionStep<'a> {
+    ///Header where node is located
+    pub source: HeaderSource,
+    ///Inspected node
+    pub node: ForwardedNode<'a>,
+    ///Whether node's IP matched filter
+    ///
+    ///This is always `false` for nodes without IP address
+    pub is_match: bool,
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, PartialEq, Eq, Debug)]
+///Record of IP resolution, intended for debugging
+///
+///Produced by `explain_filtered_forwarded_ip` and mirrors logic of `extract_filtered_forwarded_ip`
+pub struct Resolution<'a> {
+    ///Inspected nodes in order of inspection (i.e. starting from the rightmost)
+    pub steps: alloc::vec::Vec<ResolutionStep<'a>>,
+    ///Final decision
+    pub ip: Option<IpAddr>,
+}
+
+#[cfg(all(feature = "alloc", any(feature = "http", feature = "tonic014")))]
+impl<'a> Resolution<'a> {
+    pub(crate) fn explain(forwarded: impl Iterator<Item = ForwardedNode<'a>>, x_forwarded: impl Iterator<Item = ForwardedNode<'a>>, filter: &(impl crate::filter::Filter + ?Sized)) -> Self {
+        let mut result = Self {
+            steps: alloc::vec::Vec::new(),
+            ip: None,
+        };
+
+        let mut forwarded = forwarded.peekable();
+        if forwarded.peek().is_some() {
+            result.scan(HeaderSource::Forwarded, forwarded, filter);
+        } else {
+            result.scan(HeaderSource::XForwardedFor, x_forwarded, filter);
+        }
+
+        result
+    }
+
+    fn scan(&mut self, source: HeaderSource, nodes: impl Iterator<Item = ForwardedNode<'a>>, filter: &(impl crate::filter::Filter + ?Sized)) {
+        for node in nodes {
+            let is_match = match node {
+                ForwardedNode::Ip(ip) => filter.is_match(ip),
+                _ => false,
+            };
+            self.steps.push(ResolutionStep {
+                source,
+                node,
+                is_match,
+            });
+
+            if !is_match {
+                self.ip = node.ip();
+                break;
+            }
+        }
+    }
+}
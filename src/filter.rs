@@ -1,19 +1,143 @@
 //!Filtering of IP addresses
 
 use core::{marker, fmt};
-use core::net::{IpAddr, SocketAddr};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+pub mod cloud;
+pub mod country;
+#[cfg(feature = "alloc")]
+pub mod asn;
 
 ///Interface to define function that filters out IP address
 ///
 ///When match is found, IP address is skipped from being selected as client's IP (e.g. it is load balancer IP)
-pub trait Filter: Sized {
+///
+///This trait is object safe, hence it can be used as `dyn Filter`, while combinator methods are only available for sized types.
+pub trait Filter {
     ///Returns `true` if `ip` matches
     fn is_match(&self, ip: IpAddr) -> bool;
     #[inline(always)]
+    ///Returns relative cost of calling `is_match`, where lower value is cheaper
+    ///
+    ///It is used by combinators to evaluate cheaper filters first.
+    ///Exact IP comparison costs `1`, CIDR containment costs `2`, while default value is `4`.
+    ///
+    ///[Or] and [And] query it only once, when they are created, hence it is expected to be constant for the lifetime of filter.
+    fn cost_hint(&self) -> u32 {
+        4
+    }
+    #[inline(always)]
     ///Combines `self` with `right` filter in `OR` operation
-    fn or<F2: Filter>(self, right: F2) -> Or<Self, F2> {
+    fn or<F2: Filter>(self, right: F2) -> Or<Self, F2> where Self: Sized {
         or(self, right)
     }
+    #[inline(always)]
+    ///Combines `self` with `right` filter in `AND` operation
+    fn and<F2: Filter>(self, right: F2) -> And<Self, F2> where Self: Sized {
+        and(self, right)
+    }
+    #[inline(always)]
+    ///Inverts `self`, matching only IPs which `self` doesn't match
+    fn not(self) -> Not<Self> where Self: Sized {
+        not(self)
+    }
+    #[inline(always)]
+    ///Matches `self` unless IP also matches `exception`
+    fn except<E: Filter>(self, exception: E) -> Except<Self, E> where Self: Sized {
+        except(self, exception)
+    }
+}
+
+///Extension methods for every [Filter]
+pub trait FilterExt: Filter {
+    #[cfg(feature = "alloc")]
+    #[inline(always)]
+    ///Boxes filter, erasing its type
+    ///
+    ///This is useful to select filter at runtime or to store it within [FilterList]
+    fn boxed(self) -> alloc::boxed::Box<dyn Filter + Send + Sync> where Self: Sized + Send + Sync + 'static {
+        alloc::boxed::Box::new(self)
+    }
+
+    #[inline(always)]
+    ///Transforms candidate IP with `map` before passing it to `self`
+    ///
+    ///E.g. to normalize IPv4-mapped IPv6 addresses using [canonicalize_ip]
+    fn map_ip<M: Fn(IpAddr) -> IpAddr>(self, map: M) -> MapIp<Self, M> where Self: Sized {
+        MapIp {
+            filter: self,
+            map,
+        }
+    }
+}
+
+impl<F: Filter + ?Sized> FilterExt for F {}
+
+///Filter which transforms candidate IP before matching it against inner filter
+///
+///Created by [FilterExt::map_ip]
+pub struct MapIp<F, M> {
+    filter: F,
+    map: M,
+}
+
+impl<F: Filter, M: Fn(IpAddr) -> IpAddr> Filter for MapIp<F, M> {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.filter.is_match((self.map)(ip))
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        self.filter.cost_hint().saturating_add(1)
+    }
+}
+
+#[derive(Clone, Copy)]
+///Filter which uses arbitrary function `Fn(IpAddr) -> bool` to match IP
+///
+///Created by [from_fn]
+pub struct FnFilter<F>(pub F);
+
+impl<F: Fn(IpAddr) -> bool> Filter for FnFilter<F> {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        (self.0)(ip)
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+///Filter which never matches, selecting the rightmost IP
+///
+///Behaves the same way as `()`
+pub struct MatchNone;
+
+impl Filter for MatchNone {
+    #[inline(always)]
+    fn is_match(&self, _: IpAddr) -> bool {
+        false
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        0
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+///Filter which always matches, skipping every IP
+pub struct MatchAll;
+
+impl Filter for MatchAll {
+    #[inline(always)]
+    fn is_match(&self, _: IpAddr) -> bool {
+        true
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        0
+    }
 }
 
 impl Filter for () {
@@ -22,6 +146,137 @@ impl Filter for () {
     fn is_match(&self, _: IpAddr) -> bool {
         false
     }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        0
+    }
+}
+
+#[cfg(feature = "alloc")]
+///Vector of filters is matched with `OR` condition, same as slice
+impl<F: Filter> Filter for alloc::vec::Vec<F> {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.as_slice().is_match(ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        self.as_slice().cost_hint()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Filter for alloc::collections::BTreeSet<IpAddr> {
+    #[inline(always)]
+    ///Matches if set contains `ip`
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.contains(&ip)
+    }
+
+    #[inline]
+    fn cost_hint(&self) -> u32 {
+        self.len().checked_ilog2().unwrap_or(0).saturating_add(1)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: core::hash::BuildHasher> Filter for std::collections::HashSet<IpAddr, S> {
+    #[inline(always)]
+    ///Matches if set contains `ip`
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.contains(&ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        //Hashing is more expensive than plain comparison
+        2
+    }
+}
+
+impl<F: Filter + ?Sized> Filter for &F {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        F::is_match(self, ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        F::cost_hint(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F: Filter + ?Sized> Filter for alloc::boxed::Box<F> {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        F::is_match(self, ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        F::cost_hint(self)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F: Filter + ?Sized> Filter for alloc::sync::Arc<F> {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        F::is_match(self, ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        F::cost_hint(self)
+    }
+}
+
+macro_rules! impl_tuple_or {
+    ($($idx:tt: $filter:ident),+) => {
+        ///Tuple of filters is matched with `OR` condition, evaluating filters from left to right
+        impl<$($filter: Filter),+> Filter for ($($filter,)+) {
+            #[inline(always)]
+            fn is_match(&self, ip: IpAddr) -> bool {
+                $(self.$idx.is_match(ip))||+
+            }
+
+            #[inline(always)]
+            fn cost_hint(&self) -> u32 {
+                0u32$(.saturating_add(self.$idx.cost_hint()))+
+            }
+        }
+    };
+}
+
+impl_tuple_or!(0: F1);
+impl_tuple_or!(0: F1, 1: F2);
+impl_tuple_or!(0: F1, 1: F2, 2: F3);
+impl_tuple_or!(0: F1, 1: F2, 2: F3, 3: F4);
+impl_tuple_or!(0: F1, 1: F2, 2: F3, 3: F4, 4: F5);
+impl_tuple_or!(0: F1, 1: F2, 2: F3, 3: F4, 4: F5, 5: F6);
+impl_tuple_or!(0: F1, 1: F2, 2: F3, 3: F4, 4: F5, 5: F6, 6: F7);
+impl_tuple_or!(0: F1, 1: F2, 2: F3, 3: F4, 4: F5, 5: F6, 6: F7, 7: F8);
+
+impl<F: Filter> Filter for Option<F> {
+    #[inline(always)]
+    ///Matches using inner filter, if present, otherwise never matching
+    fn is_match(&self, ip: IpAddr) -> bool {
+        match self {
+            Some(filter) => filter.is_match(ip),
+            None => false,
+        }
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        match self {
+            Some(filter) => filter.cost_hint(),
+            None => 0,
+        }
+    }
 }
 
 impl Filter for IpAddr {
@@ -29,6 +284,40 @@ impl Filter for IpAddr {
     fn is_match(&self, ip: IpAddr) -> bool {
         *self == ip
     }
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        1
+    }
+}
+
+impl Filter for Ipv4Addr {
+    #[inline(always)]
+    ///Matches only IPv4 address equal to `self`
+    fn is_match(&self, ip: IpAddr) -> bool {
+        match ip {
+            IpAddr::V4(ip) => *self == ip,
+            IpAddr::V6(_) => false,
+        }
+    }
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        1
+    }
+}
+
+impl Filter for Ipv6Addr {
+    #[inline(always)]
+    ///Matches only IPv6 address equal to `self`
+    fn is_match(&self, ip: IpAddr) -> bool {
+        match ip {
+            IpAddr::V4(_) => false,
+            IpAddr::V6(ip) => *self == ip,
+        }
+    }
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        1
+    }
 }
 
 impl Filter for SocketAddr {
@@ -36,136 +325,2157 @@ impl Filter for SocketAddr {
     fn is_match(&self, ip: IpAddr) -> bool {
         self.ip() == ip
     }
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        1
+    }
 }
 
 ///Combination of filters with `OR` condition
+///
+///Evaluation is short-circuit, starting with filter which has lower [cost_hint](Filter::cost_hint).
+///If cost is equal, `left` filter is evaluated first.
+///
+///Order is determined once, when filter is created, so [cost_hint](Filter::cost_hint) is not queried on every match.
 pub struct Or<F1, F2> {
     left: F1,
     right: F2,
+    cost: u32,
+    is_right_first: bool,
 }
 
 impl<F1: Filter, F2: Filter> Filter for Or<F1, F2> {
     #[inline(always)]
     fn is_match(&self, ip: IpAddr) -> bool {
-        self.left.is_match(ip) || self.right.is_match(ip)
+        if self.is_right_first {
+            self.right.is_match(ip) || self.left.is_match(ip)
+        } else {
+            self.left.is_match(ip) || self.right.is_match(ip)
+        }
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        self.cost
     }
 }
 
-///Collection of filters which are matched with `OR` condition
+///Combination of filters with `AND` condition
 ///
-///`I` must be type that implements `AsRef<[impl Filter]>`
-pub struct CollectionOr<I, F> {
-    collection: I,
-    _filter: marker::PhantomData<F>,
+///Evaluation is short-circuit, starting with filter which has lower [cost_hint](Filter::cost_hint).
+///If cost is equal, `left` filter is evaluated first.
+///
+///Order is determined once, when filter is created, so [cost_hint](Filter::cost_hint) is not queried on every match.
+pub struct And<F1, F2> {
+    left: F1,
+    right: F2,
+    cost: u32,
+    is_right_first: bool,
 }
 
-impl<F: Filter, I: AsRef<[F]>> CollectionOr<I, F> {
+impl<F1: Filter, F2: Filter> Filter for And<F1, F2> {
     #[inline(always)]
-    ///Creates new collection
-    pub const fn new(collection: I) -> Self {
-        Self {
-            collection,
-            _filter: marker::PhantomData
+    fn is_match(&self, ip: IpAddr) -> bool {
+        if self.is_right_first {
+            self.right.is_match(ip) && self.left.is_match(ip)
+        } else {
+            self.left.is_match(ip) && self.right.is_match(ip)
         }
     }
-}
 
-impl<F: Filter, I: AsRef<[F]>> Filter for CollectionOr<I, F> {
     #[inline(always)]
-    fn is_match(&self, ip: IpAddr) -> bool {
-        self.collection.as_ref().iter().any(|filter| filter.is_match(ip))
+    fn cost_hint(&self) -> u32 {
+        self.cost
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-//Possible errors parsing CIDR
-enum ParseError<'a> {
-    //Error parsing CIDR expression
-    ParseError(ip_cidr::ParseError<'a>),
-    //CIDR expression is valid, but its prefix does not fit type of IP address
-    InvalidPrefix
+///Inversion of filter, matching only IPs which inner filter doesn't match
+pub struct Not<F> {
+    filter: F,
 }
 
-#[repr(transparent)]
-#[derive(PartialEq, Eq)]
-///Error which is returned when parsing CIDR's textual representation
-pub struct CidrParseError<'a>(ParseError<'a>);
+impl<F: Filter> Filter for Not<F> {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        !self.filter.is_match(ip)
+    }
 
-impl fmt::Debug for CidrParseError<'_> {
     #[inline(always)]
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(&self.0, fmt)
+    fn cost_hint(&self) -> u32 {
+        self.filter.cost_hint()
     }
 }
 
-impl fmt::Display for CidrParseError<'_> {
+///Filter which matches `filter` unless IP also matches `exception`
+///
+///Created by [except] or [Filter::except]
+pub struct Except<F, E> {
+    filter: F,
+    exception: E,
+}
+
+impl<F: Filter, E: Filter> Filter for Except<F, E> {
     #[inline(always)]
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &self.0 {
-            ParseError::InvalidPrefix => fmt.write_str("Invalid CIDR prefix"),
-            ParseError::ParseError(error) => fmt::Display::fmt(error, fmt),
-        }
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.filter.is_match(ip) && !self.exception.is_match(ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        self.filter.cost_hint().saturating_add(self.exception.cost_hint())
     }
 }
 
-#[repr(transparent)]
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-///CIDR filter
-pub struct Cidr(ip_cidr::Cidr);
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Precedence of [Policy] lists, when IP is within both of them
+pub enum Precedence {
+    ///Denylist overrides allowlist
+    DenyOverrides,
+    ///Allowlist overrides denylist
+    AllowOverrides,
+}
 
-impl Cidr {
-    #[inline]
-    ///Creates new instance from textual representation
-    pub const fn from_text(text: &str) -> Result<Self, CidrParseError<'_>> {
-        match ip_cidr::parse_cidr(text) {
-            Ok(Some(inner)) => Ok(Self(inner)),
-            Ok(None) => Err(CidrParseError(ParseError::InvalidPrefix)),
-            Err(error) => Err(CidrParseError(ParseError::ParseError(error))),
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Decision of [Policy]
+pub enum Verdict {
+    ///IP is allowed
+    Allow,
+    ///IP is denied
+    Deny,
+    ///IP is in neither of lists
+    Unlisted,
+}
+
+#[derive(Clone, Debug)]
+///Policy combining allowlist and denylist with explicit precedence
+///
+///As [Filter] it matches only IPs with [Verdict::Allow]
+pub struct Policy<A, D> {
+    ///Allowlist
+    pub allow: A,
+    ///Denylist
+    pub deny: D,
+    ///Precedence when IP is within both lists
+    pub precedence: Precedence,
+}
+
+impl<A: Filter, D: Filter> Policy<A, D> {
+    #[inline(always)]
+    ///Creates new policy
+    pub const fn new(allow: A, deny: D, precedence: Precedence) -> Self {
+        Self {
+            allow,
+            deny,
+            precedence,
         }
     }
 
-    #[inline]
-    ///Creates new instance from IP and prefix, returning error if `prefix` is invalid
-    pub const fn new(ip: IpAddr, prefix: u8) -> Result<Self, CidrParseError<'static>> {
-        match ip_cidr::Cidr::new(ip, prefix) {
-            Some(cidr) => Ok(Self(cidr)),
-            None => Err(CidrParseError(ParseError::InvalidPrefix)),
+    ///Determines verdict for `ip`
+    pub fn verdict(&self, ip: IpAddr) -> Verdict {
+        match self.precedence {
+            Precedence::DenyOverrides => if self.deny.is_match(ip) {
+                Verdict::Deny
+            } else if self.allow.is_match(ip) {
+                Verdict::Allow
+            } else {
+                Verdict::Unlisted
+            },
+            Precedence::AllowOverrides => if self.allow.is_match(ip) {
+                Verdict::Allow
+            } else if self.deny.is_match(ip) {
+                Verdict::Deny
+            } else {
+                Verdict::Unlisted
+            },
         }
     }
 }
 
-impl Filter for Cidr {
+impl<A: Filter, D: Filter> Filter for Policy<A, D> {
     #[inline(always)]
     fn is_match(&self, ip: IpAddr) -> bool {
-        self.0.contains(ip)
+        self.verdict(ip) == Verdict::Allow
     }
-}
 
-impl fmt::Debug for Cidr {
     #[inline(always)]
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Debug::fmt(&self.0, fmt)
+    fn cost_hint(&self) -> u32 {
+        self.allow.cost_hint().saturating_add(self.deny.cost_hint())
     }
 }
 
-impl fmt::Display for Cidr {
+///Collection of filters which are matched with `OR` condition
+///
+///`I` must be type that implements `AsRef<[impl Filter]>`
+pub struct CollectionOr<I, F> {
+    collection: I,
+    _filter: marker::PhantomData<F>,
+}
+
+impl<F: Filter, I: AsRef<[F]>> CollectionOr<I, F> {
     #[inline(always)]
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt::Display::fmt(&self.0, fmt)
+    ///Creates new collection
+    pub const fn new(collection: I) -> Self {
+        Self {
+            collection,
+            _filter: marker::PhantomData
+        }
     }
 }
 
-#[inline]
-///Creates new `OR` filter out of two filters
-pub const fn or<F1, F2>(left: F1, right: F2) -> Or<F1, F2> {
-    Or {
-        left,
-        right
+impl<F: Filter, I: AsRef<[F]>> Filter for CollectionOr<I, F> {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.collection.as_ref().iter().any(|filter| filter.is_match(ip))
     }
-}
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        self.collection.as_ref().cost_hint()
+    }
+}
+
+///Slice of filters is matched with `OR` condition, same as [CollectionOr]
+impl<F: Filter> Filter for [F] {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.iter().any(|filter| filter.is_match(ip))
+    }
+
+    #[inline]
+    fn cost_hint(&self) -> u32 {
+        self.iter().fold(0u32, |cost, filter| cost.saturating_add(filter.cost_hint()))
+    }
+}
+
+///Array of filters is matched with `OR` condition, same as [CollectionOr]
+impl<F: Filter, const N: usize> Filter for [F; N] {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.as_slice().is_match(ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        self.as_slice().cost_hint()
+    }
+}
+
+///Set of individual IP addresses, matching if IP is equal to any of the members
+///
+///`I` must be type that implements `AsRef<[IpAddr]>`, which includes both slices and arrays.
+///
+///## Sorted input
+///
+///When set is created via [new_sorted](#method.new_sorted), lookup is performed using binary search.
+///Otherwise set is scanned linearly, which is fine for a small number of addresses
+///
+///## Const context
+///
+///[new_sorted_checked](#method.new_sorted_checked) and [contains_const](#method.contains_const) are only available for `IpSet<&[IpAddr]>`.
+///Array must be borrowed as slice in order to use them (e.g. `IpSet::new_sorted_checked(&PROXIES)`).
+pub struct IpSet<I> {
+    collection: I,
+    is_sorted: bool,
+}
+
+impl<I: AsRef<[IpAddr]>> IpSet<I> {
+    #[inline(always)]
+    ///Creates new set out of `collection` in arbitrary order
+    pub const fn new(collection: I) -> Self {
+        Self {
+            collection,
+            is_sorted: false,
+        }
+    }
+
+    #[inline(always)]
+    ///Creates new set out of `collection`, which must be sorted in ascending order
+    ///
+    ///If `collection` is not sorted, then lookup result is unspecified
+    pub const fn new_sorted(collection: I) -> Self {
+        Self {
+            collection,
+            is_sorted: true,
+        }
+    }
+}
+
+//Orders IPs same as `Ord` for `IpAddr`, but usable in const context
+const fn cmp_ip(left: &IpAddr, right: &IpAddr) -> core::cmp::Ordering {
+    use core::cmp::Ordering;
+
+    match (left, right) {
+        (IpAddr::V4(left), IpAddr::V4(right)) => {
+            let (left, right) = (left.to_bits(), right.to_bits());
+            if left < right {
+                Ordering::Less
+            } else if left > right {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        },
+        (IpAddr::V6(left), IpAddr::V6(right)) => {
+            let (left, right) = (left.to_bits(), right.to_bits());
+            if left < right {
+                Ordering::Less
+            } else if left > right {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        },
+        (IpAddr::V4(_), IpAddr::V6(_)) => Ordering::Less,
+        (IpAddr::V6(_), IpAddr::V4(_)) => Ordering::Greater,
+    }
+}
+
+impl<'a> IpSet<&'a [IpAddr]> {
+    ///Creates new set out of `collection`, verifying it is sorted in ascending order
+    ///
+    ///Returns `None` if `collection` is not sorted, which allows to fail compilation when used in `const` context
+    pub const fn new_sorted_checked(collection: &'a [IpAddr]) -> Option<Self> {
+        let mut idx = 1;
+        while idx < collection.len() {
+            if cmp_ip(&collection[idx - 1], &collection[idx]).is_gt() {
+                return None;
+            }
+            idx += 1;
+        }
+
+        Some(Self::new_sorted(collection))
+    }
+
+    ///Checks whether `ip` is within set
+    ///
+    ///This is the same as `Filter::is_match`, but usable in `const` context
+    pub const fn contains_const(&self, ip: IpAddr) -> bool {
+        let collection = self.collection;
+        if self.is_sorted {
+            let mut low = 0;
+            let mut high = collection.len();
+            while low < high {
+                let mid = low + (high - low) / 2;
+                match cmp_ip(&collection[mid], &ip) {
+                    core::cmp::Ordering::Less => low = mid + 1,
+                    core::cmp::Ordering::Greater => high = mid,
+                    core::cmp::Ordering::Equal => return true,
+                }
+            }
+        } else {
+            let mut idx = 0;
+            while idx < collection.len() {
+                if cmp_ip(&collection[idx], &ip).is_eq() {
+                    return true;
+                }
+                idx += 1;
+            }
+        }
+
+        false
+    }
+}
+
+impl<I: AsRef<[IpAddr]>> Filter for IpSet<I> {
+    #[inline]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        let collection = self.collection.as_ref();
+        if self.is_sorted {
+            collection.binary_search(&ip).is_ok()
+        } else {
+            collection.contains(&ip)
+        }
+    }
+
+    #[inline]
+    fn cost_hint(&self) -> u32 {
+        let len = self.collection.as_ref().len();
+        if self.is_sorted {
+            len.checked_ilog2().map_or(1, |depth| depth.saturating_add(1))
+        } else {
+            len.try_into().unwrap_or(u32::MAX)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+//Possible errors parsing CIDR
+enum ParseError<'a> {
+    //Error parsing CIDR expression
+    ParseError(ip_cidr::ParseError<'a>),
+    //CIDR expression is valid, but its prefix does not fit type of IP address
+    InvalidPrefix,
+    //CIDR expression is valid, but its prefix is broader than allowed
+    PrefixTooBroad,
+    //Number of CIDR expressions in list doesn't match expected
+    CountMismatch,
+}
+
+#[repr(transparent)]
+#[derive(PartialEq, Eq)]
+///Error which is returned when parsing CIDR's textual representation
+pub struct CidrParseError<'a>(ParseError<'a>);
+
+impl fmt::Debug for CidrParseError<'_> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, fmt)
+    }
+}
+
+impl fmt::Display for CidrParseError<'_> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            ParseError::InvalidPrefix => fmt.write_str("Invalid CIDR prefix"),
+            ParseError::PrefixTooBroad => fmt.write_str("CIDR prefix is broader than allowed"),
+            ParseError::CountMismatch => fmt.write_str("Number of CIDR entries doesn't match expected"),
+            ParseError::ParseError(error) => fmt::Display::fmt(error, fmt),
+        }
+    }
+}
+
+impl CidrParseError<'_> {
+    ///Detaches error from input, making it `'static`
+    ///
+    ///Borrowed fragment of invalid input, if any, is not retained, while the rest of information is preserved.
+    pub const fn into_owned(self) -> CidrParseError<'static> {
+        use ip_cidr::ParseError as IpError;
+
+        CidrParseError(match self.0 {
+            ParseError::ParseError(error) => ParseError::ParseError(match error {
+                IpError::InvalidComponent(_) => IpError::InvalidComponent(""),
+                IpError::InvalidCidr(_) => IpError::InvalidCidr(""),
+                IpError::UnexpectedCharacter(ch, pos) => IpError::UnexpectedCharacter(ch, pos),
+                IpError::InvalidIp => IpError::InvalidIp,
+                IpError::InvalidIpv4 => IpError::InvalidIpv4,
+                IpError::Ipv4InvalidComponentSize(size) => IpError::Ipv4InvalidComponentSize(size),
+                IpError::InvalidIpv6 => IpError::InvalidIpv6,
+                IpError::Ipv6InvalidComponentSize(size) => IpError::Ipv6InvalidComponentSize(size),
+                IpError::Ipv6MultipleZeroAbbrv => IpError::Ipv6MultipleZeroAbbrv,
+                IpError::NonAsciiCharacter(pos) => IpError::NonAsciiCharacter(pos),
+                IpError::MissingIp => IpError::MissingIp,
+                IpError::MissingCidr => IpError::MissingCidr,
+                IpError::Ipv4CidrPrefixOverflow(prefix) => IpError::Ipv4CidrPrefixOverflow(prefix),
+                IpError::Ipv6CidrPrefixOverflow(prefix) => IpError::Ipv6CidrPrefixOverflow(prefix),
+            }),
+            ParseError::InvalidPrefix => ParseError::InvalidPrefix,
+            ParseError::PrefixTooBroad => ParseError::PrefixTooBroad,
+            ParseError::CountMismatch => ParseError::CountMismatch,
+        })
+    }
+}
+
+impl core::error::Error for CidrParseError<'_> {
+}
+
+#[macro_export]
+///Creates array of [Cidr](filter/struct.Cidr.html) out of string literals, parsing them at compile time
+///
+///Invalid CIDR fails compilation.
+///
+///```rust
+///use http_ip::filter::{Cidr, CidrSet};
+///
+///const TRUSTED: [Cidr; 2] = http_ip::cidrs!["10.0.0.0/8", "192.168.0.0/16"];
+///const TRUSTED_SET: CidrSet<&[Cidr]> = CidrSet::new(&TRUSTED);
+///```
+macro_rules! cidrs {
+    ($($text:literal),* $(,)?) => {
+        [$(
+            const {
+                match $crate::filter::Cidr::from_text($text) {
+                    Ok(cidr) => cidr,
+                    Err(_) => panic!(concat!("Invalid CIDR: ", $text)),
+                }
+            }
+        ),*]
+    };
+}
+
+#[repr(transparent)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+///CIDR filter
+pub struct Cidr(ip_cidr::Cidr);
+
+impl Cidr {
+    #[inline]
+    ///Creates new instance from textual representation
+    ///
+    ///Bare IP address without prefix is accepted as single host, i.e. `/32` for IPv4 and `/128` for IPv6,
+    ///which allows to parse config with mixed entries (e.g. `10.0.0.1` and `10.0.0.0/8`)
+    pub const fn from_text(text: &str) -> Result<Self, CidrParseError<'_>> {
+        match ip_cidr::parse_cidr(text) {
+            Ok(Some(inner)) => Ok(Self(inner)),
+            Ok(None) => Err(CidrParseError(ParseError::InvalidPrefix)),
+            Err(error) => Err(CidrParseError(ParseError::ParseError(error))),
+        }
+    }
+
+    #[inline]
+    ///Creates new instance from textual representation, rejecting CIDR with prefix shorter than `min_prefix`
+    ///
+    ///This guards against accidentally broad rules (e.g. `10.0.0.0/1` instead of `10.0.0.0/16`).
+    ///`min_prefix` is applied regardless of IP family.
+    pub const fn from_text_bounded(text: &str, min_prefix: u8) -> Result<Self, CidrParseError<'_>> {
+        match Self::from_text(text) {
+            Ok(cidr) => if cidr.prefix() < min_prefix {
+                Err(CidrParseError(ParseError::PrefixTooBroad))
+            } else {
+                Ok(cidr)
+            },
+            Err(error) => Err(error),
+        }
+    }
+
+    ///Parses list of exactly `N` CIDR expressions, separated by commas and/or ASCII whitespace
+    ///
+    ///Empty entries are ignored, while each entry is parsed as in [from_text](#method.from_text).
+    ///This allows to embed list (e.g. `10.0.0.0/8, 172.16.0.0/12` or one CIDR per line) verbatim in `const` context.
+    pub const fn from_text_list<const N: usize>(text: &str) -> Result<[Self; N], CidrParseError<'_>> {
+        const PLACEHOLDER: Cidr = match Cidr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 0) {
+            Ok(cidr) => cidr,
+            Err(_) => unreachable!(),
+        };
+
+        let mut result = [PLACEHOLDER; N];
+        let mut count = 0;
+        let mut rest = text.as_bytes();
+        loop {
+            let mut idx = 0;
+            while idx < rest.len() && rest[idx] != b',' && !rest[idx].is_ascii_whitespace() {
+                idx += 1;
+            }
+
+            let (entry, tail) = rest.split_at(idx);
+            let entry = match core::str::from_utf8(entry) {
+                Ok(entry) => entry,
+                //Split happens only at ASCII separators, hence entry is always valid UTF-8
+                Err(_) => unreachable!(),
+            };
+
+            if !entry.is_empty() {
+                if count == N {
+                    return Err(CidrParseError(ParseError::CountMismatch));
+                }
+
+                match Self::from_text(entry) {
+                    Ok(cidr) => {
+                        result[count] = cidr;
+                        count += 1;
+                    },
+                    Err(error) => return Err(error),
+                }
+            }
+
+            match tail.split_first() {
+                Some((_, tail)) => rest = tail,
+                None => break,
+            }
+        }
+
+        if count == N {
+            Ok(result)
+        } else {
+            Err(CidrParseError(ParseError::CountMismatch))
+        }
+    }
+
+    #[inline]
+    ///Creates new instance from IP and prefix, returning error if `prefix` is invalid
+    pub const fn new(ip: IpAddr, prefix: u8) -> Result<Self, CidrParseError<'static>> {
+        match ip_cidr::Cidr::new(ip, prefix) {
+            Some(cidr) => Ok(Self(cidr)),
+            None => Err(CidrParseError(ParseError::InvalidPrefix)),
+        }
+    }
+
+    #[inline(always)]
+    ///Checks whether `ip` is within CIDR block
+    ///
+    ///This is the same as `Filter::is_match`, but usable in `const` context
+    pub const fn contains_const(&self, ip: IpAddr) -> bool {
+        self.0.contains(ip)
+    }
+
+    #[inline(always)]
+    ///Checks whether `other` CIDR block is fully contained within this block
+    pub const fn contains_cidr(&self, other: &Cidr) -> bool {
+        self.prefix() <= other.prefix() && self.0.contains(other.network_addr())
+    }
+
+    #[inline(always)]
+    ///Returns prefix length
+    pub const fn prefix(&self) -> u8 {
+        self.0.prefix()
+    }
+
+    #[inline(always)]
+    ///Returns network address, which is lowest possible address within CIDR block
+    pub const fn network_addr(&self) -> IpAddr {
+        self.0.network_addr()
+    }
+}
+
+impl core::str::FromStr for Cidr {
+    ///Owned error, which doesn't retain fragment of invalid input
+    type Err = CidrParseError<'static>;
+
+    #[inline]
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::from_text(text).map_err(|error| error.into_owned())
+    }
+}
+
+impl TryFrom<&str> for Cidr {
+    type Error = CidrParseError<'static>;
+
+    #[inline(always)]
+    fn try_from(text: &str) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl Filter for Cidr {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.0.contains(ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        2
+    }
+}
+
+impl fmt::Debug for Cidr {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, fmt)
+    }
+}
+
+impl fmt::Display for Cidr {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, fmt)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+///Set of CIDR blocks, matching if IP is within any of the blocks
+///
+///`I` must be type that implements `AsRef<[Cidr]>`, which includes both slices and arrays.
+///
+///Blocks are scanned linearly, consider `CidrTrie` for large number of blocks.
+pub struct CidrSet<I> {
+    collection: I,
+}
+
+impl<I> CidrSet<I> {
+    #[inline(always)]
+    ///Creates new set out of CIDR blocks
+    pub const fn new(collection: I) -> Self {
+        Self {
+            collection,
+        }
+    }
+}
+
+impl<I: AsRef<[Cidr]>> CidrSet<I> {
+    #[inline(always)]
+    ///Returns CIDR blocks of the set
+    pub fn cidrs(&self) -> &[Cidr] {
+        self.collection.as_ref()
+    }
+}
+
+impl CidrSet<&'_ [Cidr]> {
+    ///Checks whether `ip` is within any of the blocks
+    ///
+    ///This is the same as `Filter::is_match`, but usable in `const` context
+    pub const fn contains_const(&self, ip: IpAddr) -> bool {
+        let mut idx = 0;
+        while idx < self.collection.len() {
+            if self.collection[idx].contains_const(ip) {
+                return true;
+            }
+            idx += 1;
+        }
+
+        false
+    }
+}
+
+const PRIVATE_NETWORKS_CIDRS: [Cidr; 5] = crate::cidrs![
+    "10.0.0.0/8",
+    "172.16.0.0/12",
+    "192.168.0.0/16",
+    //CGNAT
+    "100.64.0.0/10",
+    //Unique local
+    "fc00::/7",
+];
+const LOOPBACK_CIDRS: [Cidr; 2] = crate::cidrs!["127.0.0.0/8", "::1/128"];
+const LINK_LOCAL_CIDRS: [Cidr; 2] = crate::cidrs!["169.254.0.0/16", "fe80::/10"];
+const NON_PUBLIC_CIDRS: [Cidr; 9] = [
+    PRIVATE_NETWORKS_CIDRS[0],
+    PRIVATE_NETWORKS_CIDRS[1],
+    PRIVATE_NETWORKS_CIDRS[2],
+    PRIVATE_NETWORKS_CIDRS[3],
+    PRIVATE_NETWORKS_CIDRS[4],
+    LOOPBACK_CIDRS[0],
+    LOOPBACK_CIDRS[1],
+    LINK_LOCAL_CIDRS[0],
+    LINK_LOCAL_CIDRS[1],
+];
+
+///Private networks: RFC 1918 (`10.0.0.0/8`, `172.16.0.0/12`, `192.168.0.0/16`), CGNAT (`100.64.0.0/10`) and IPv6 unique local (`fc00::/7`)
+pub const PRIVATE_NETWORKS: CidrSet<&[Cidr]> = CidrSet::new(&PRIVATE_NETWORKS_CIDRS);
+///Loopback: `127.0.0.0/8` and `::1`
+pub const LOOPBACK: CidrSet<&[Cidr]> = CidrSet::new(&LOOPBACK_CIDRS);
+///Link local: `169.254.0.0/16` and `fe80::/10`
+pub const LINK_LOCAL: CidrSet<&[Cidr]> = CidrSet::new(&LINK_LOCAL_CIDRS);
+///Combination of [PRIVATE_NETWORKS], [LOOPBACK] and [LINK_LOCAL]
+///
+///This is enough to skip internal proxies in most common setups
+pub const NON_PUBLIC: CidrSet<&[Cidr]> = CidrSet::new(&NON_PUBLIC_CIDRS);
+
+const BOGONS_EXTRA_CIDRS: [Cidr; 14] = crate::cidrs![
+    //"This" network
+    "0.0.0.0/8",
+    //IETF protocol assignments
+    "192.0.0.0/24",
+    //TEST-NET-1
+    "192.0.2.0/24",
+    //Benchmarking
+    "198.18.0.0/15",
+    //TEST-NET-2
+    "198.51.100.0/24",
+    //TEST-NET-3
+    "203.0.113.0/24",
+    //Multicast
+    "224.0.0.0/4",
+    //Reserved, including broadcast
+    "240.0.0.0/4",
+    //Unspecified
+    "::/128",
+    //Discard only
+    "100::/64",
+    //Benchmarking
+    "2001:2::/48",
+    //Documentation
+    "2001:db8::/32",
+    "3fff::/20",
+    //Multicast
+    "ff00::/8",
+];
+const BOGONS_CIDRS: [Cidr; NON_PUBLIC_CIDRS.len() + BOGONS_EXTRA_CIDRS.len()] = {
+    let mut result = [NON_PUBLIC_CIDRS[0]; NON_PUBLIC_CIDRS.len() + BOGONS_EXTRA_CIDRS.len()];
+    let mut idx = 0;
+    while idx < NON_PUBLIC_CIDRS.len() {
+        result[idx] = NON_PUBLIC_CIDRS[idx];
+        idx += 1;
+    }
+    let mut idx = 0;
+    while idx < BOGONS_EXTRA_CIDRS.len() {
+        result[NON_PUBLIC_CIDRS.len() + idx] = BOGONS_EXTRA_CIDRS[idx];
+        idx += 1;
+    }
+    result
+};
+
+///Bogons: addresses which cannot legitimately appear as source on public internet
+///
+///In addition to [NON_PUBLIC] it covers unspecified, documentation (TEST-NET), benchmarking, multicast and reserved ranges.
+///Use it to detect chain entries claiming to originate from such addresses.
+pub const BOGONS: CidrSet<&[Cidr]> = CidrSet::new(&BOGONS_CIDRS);
+
+impl<I: AsRef<[Cidr]>> Filter for CidrSet<I> {
+    #[inline]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.collection.as_ref().iter().any(|cidr| cidr.0.contains(ip))
+    }
+
+    #[inline]
+    fn cost_hint(&self) -> u32 {
+        u32::try_from(self.collection.as_ref().len()).unwrap_or(u32::MAX).saturating_mul(2)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+///Filter matching IP addresses that share first `prefix` bits with `reference`
+///
+///This is effectively CIDR anchored on runtime IP, e.g. to match anything within the same `/24` as known proxy.
+///
+///Prefix larger than length of the address is treated as full length. IP of different family never matches.
+pub struct SamePrefix {
+    ///IP to compare with
+    pub reference: IpAddr,
+    ///Number of leading bits to compare
+    pub prefix: u8,
+}
+
+impl SamePrefix {
+    #[inline(always)]
+    ///Creates new instance
+    pub const fn new(reference: IpAddr, prefix: u8) -> Self {
+        Self {
+            reference,
+            prefix,
+        }
+    }
+}
+
+impl Filter for SamePrefix {
+    #[inline]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        match (self.reference, ip) {
+            (IpAddr::V4(reference), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(u32::BITS.saturating_sub(self.prefix as u32)).unwrap_or(0);
+                (reference.to_bits() & mask) == (ip.to_bits() & mask)
+            },
+            (IpAddr::V6(reference), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(u128::BITS.saturating_sub(self.prefix as u32)).unwrap_or(0);
+                (reference.to_bits() & mask) == (ip.to_bits() & mask)
+            },
+            _ => false,
+        }
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        2
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+///Inclusive range of IP addresses, matching if IP is within `start..=end`
+///
+///Useful for address pools that do not align to CIDR boundaries
+pub struct IpRange {
+    start: IpAddr,
+    end: IpAddr,
+}
+
+impl IpRange {
+    ///Creates new range out of inclusive bounds
+    ///
+    ///Returns `None` if `start` and `end` belong to different address families or if `start` is greater than `end`
+    pub const fn new(start: IpAddr, end: IpAddr) -> Option<Self> {
+        let is_valid = match (start, end) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => start.to_bits() <= end.to_bits(),
+            (IpAddr::V6(start), IpAddr::V6(end)) => start.to_bits() <= end.to_bits(),
+            _ => false,
+        };
+
+        if is_valid {
+            Some(Self {
+                start,
+                end
+            })
+        } else {
+            None
+        }
+    }
+
+    #[inline(always)]
+    ///Returns first address of the range
+    pub const fn start(&self) -> IpAddr {
+        self.start
+    }
+
+    #[inline(always)]
+    ///Returns last address of the range
+    pub const fn end(&self) -> IpAddr {
+        self.end
+    }
+
+    #[inline]
+    ///Returns iterator over minimal set of CIDR blocks, which together cover exactly this range
+    pub const fn to_cidrs(&self) -> RangeCidrs {
+        let (current, end, is_v4) = match (self.start, self.end) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => (start.to_bits() as u128, end.to_bits() as u128, true),
+            (IpAddr::V6(start), IpAddr::V6(end)) => (start.to_bits(), end.to_bits(), false),
+            //Constructor guarantees both bounds are of the same family
+            _ => (1, 0, true),
+        };
+
+        RangeCidrs {
+            current: Some(current),
+            end,
+            is_v4,
+        }
+    }
+
+    ///Returns `true` if `ip` is within range
+    pub const fn contains(&self, ip: IpAddr) -> bool {
+        match (self.start, self.end, ip) {
+            (IpAddr::V4(start), IpAddr::V4(end), IpAddr::V4(ip)) => start.to_bits() <= ip.to_bits() && ip.to_bits() <= end.to_bits(),
+            (IpAddr::V6(start), IpAddr::V6(end), IpAddr::V6(ip)) => start.to_bits() <= ip.to_bits() && ip.to_bits() <= end.to_bits(),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+///Iterator over CIDR blocks covering [IpRange], created by [IpRange::to_cidrs]
+///
+///Blocks are yielded in ascending order
+pub struct RangeCidrs {
+    //None when range is exhausted
+    current: Option<u128>,
+    end: u128,
+    is_v4: bool,
+}
+
+impl Iterator for RangeCidrs {
+    type Item = Cidr;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.current?;
+        if current > self.end {
+            self.current = None;
+            return None;
+        }
+
+        let bits = if self.is_v4 {
+            u32::BITS
+        } else {
+            u128::BITS
+        };
+
+        //Largest block aligned at current address
+        let align = current.trailing_zeros().min(bits);
+        //Largest block that doesn't go past end
+        let remaining = self.end - current;
+        let fit = match remaining.checked_add(1) {
+            Some(len) => len.ilog2(),
+            None => u128::BITS,
+        };
+        let size = align.min(fit);
+
+        self.current = 1u128.checked_shl(size).and_then(|len| current.checked_add(len));
+        let ip = if self.is_v4 {
+            IpAddr::V4(Ipv4Addr::from_bits(current as u32))
+        } else {
+            IpAddr::V6(Ipv6Addr::from_bits(current))
+        };
+        Cidr::new(ip, (bits - size) as u8).ok()
+    }
+}
+
+impl core::iter::FusedIterator for RangeCidrs {}
+
+impl Filter for IpRange {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.contains(ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        2
+    }
+}
+
+///Converts IPv4-mapped (`::ffff:a.b.c.d`) and IPv4-compatible (`::a.b.c.d`) IPv6 addresses to IPv4
+///
+///Other addresses, including `::` and `::1`, are returned unchanged.
+pub const fn canonicalize_ip(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(_) => ip,
+        IpAddr::V6(ipv6) => match ipv6.to_ipv4_mapped() {
+            Some(ipv4) => IpAddr::V4(ipv4),
+            None => {
+                let [a, b, c, d, e, f, _, _] = ipv6.segments();
+                if a == 0 && b == 0 && c == 0 && d == 0 && e == 0 && f == 0 && !ipv6.is_unspecified() && !ipv6.is_loopback() {
+                    match ipv6.to_ipv4() {
+                        Some(ipv4) => IpAddr::V4(ipv4),
+                        None => ip,
+                    }
+                } else {
+                    ip
+                }
+            }
+        }
+    }
+}
+
+///Checks whether `ip` is globally routable address
+///
+///**Unlike `std`, documentation ranges are considered global**: `192.0.2.0/24`, `198.51.100.0/24`, `203.0.113.0/24`,
+///`2001:db8::/32` and `3fff::/20` are accepted, as they are commonly used as client addresses in examples and tests.
+///Use [BOGONS] when such addresses should be rejected too.
+///
+///Otherwise this is approximation of unstable `IpAddr::is_global`, rejecting:
+///
+///- IPv4: `0.0.0.0/8`, private, shared (`100.64.0.0/10`), loopback, link-local, IETF protocol assignments (`192.0.0.0/24`),
+///benchmarking (`198.18.0.0/15`), reserved (`240.0.0.0/4`), broadcast and multicast;
+///- IPv6: unspecified, loopback, local-use IPv4/IPv6 translation (`64:ff9b:1::/48`), discard-only (`100::/64`),
+///IETF protocol assignments (`2001::/23`, including Teredo and benchmarking), 6to4 (`2002::/16`), segment routing (`5f00::/16`),
+///unique local (`fc00::/7`), link-local (`fe80::/10`) and multicast;
+///- IPv4-mapped IPv6 addresses are checked as IPv4.
+///
+///Same as `std`, globally reachable assignments within special purpose blocks are accepted:
+///`192.0.0.9`, `192.0.0.10`, `2001:1::1`, `2001:1::2`, `2001:3::/32`, `2001:4:112::/48`, `2001:20::/28` and `2001:30::/28`.
+///
+///Unlike `std`, multicast is rejected regardless of scope, as it cannot be source of request.
+pub const fn is_global(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => is_global_v4(ip),
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_global_v4(ip),
+            None => {
+                let segments = ip.segments();
+                !(ip.is_unspecified()
+                  || ip.is_loopback()
+                  || ip.is_multicast()
+                  //Unique local fc00::/7
+                  || (segments[0] & 0xfe00) == 0xfc00
+                  //Link local fe80::/10
+                  || (segments[0] & 0xffc0) == 0xfe80
+                  //IPv4/IPv6 translation for local use 64:ff9b:1::/48
+                  || (segments[0] == 0x64 && segments[1] == 0xff9b && segments[2] == 1)
+                  //Discard-only 100::/64
+                  || (segments[0] == 0x100 && segments[1] == 0 && segments[2] == 0 && segments[3] == 0)
+                  //IETF protocol assignments 2001::/23
+                  || (segments[0] == 0x2001 && segments[1] < 0x200 && !is_global_ietf_v6(ip))
+                  //6to4 2002::/16
+                  || segments[0] == 0x2002
+                  //Segment routing 5f00::/16
+                  || segments[0] == 0x5f00)
+            }
+        },
+    }
+}
+
+//Checks whether `ip` within IETF protocol assignments `2001::/23` is globally reachable
+const fn is_global_ietf_v6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+    //PCP and TURN anycast 2001:1::1 and 2001:1::2
+    (segments[1] == 1 && segments[2] == 0 && segments[3] == 0 && segments[4] == 0 && segments[5] == 0 && segments[6] == 0 && (segments[7] == 1 || segments[7] == 2))
+    //AMT 2001:3::/32
+    || segments[1] == 3
+    //AS112-v6 2001:4:112::/48
+    || (segments[1] == 4 && segments[2] == 0x112)
+    //ORCHIDv2 2001:20::/28 and drone remote ID 2001:30::/28
+    || (segments[1] >= 0x20 && segments[1] <= 0x3f)
+}
+
+const fn is_global_v4(ip: Ipv4Addr) -> bool {
+    let octets = ip.octets();
+    !(octets[0] == 0
+      || ip.is_private()
+      || ip.is_loopback()
+      || ip.is_link_local()
+      || ip.is_broadcast()
+      || ip.is_multicast()
+      //Shared 100.64.0.0/10
+      || (octets[0] == 100 && (octets[1] & 0b1100_0000) == 0b0100_0000)
+      //IETF protocol assignments 192.0.0.0/24, except PCP and TURN anycast
+      || (octets[0] == 192 && octets[1] == 0 && octets[2] == 0 && octets[3] != 9 && octets[3] != 10)
+      //Benchmarking 198.18.0.0/15
+      || (octets[0] == 198 && (octets[1] & 0xfe) == 18)
+      //Reserved 240.0.0.0/4
+      || (octets[0] & 0xf0) == 240)
+}
+
+//Returns combined cost of two filters and whether `right` should be evaluated first
+fn evaluation_order(left: &impl Filter, right: &impl Filter) -> (u32, bool) {
+    let left = left.cost_hint();
+    let right = right.cost_hint();
+    (left.saturating_add(right), right < left)
+}
+
+#[inline]
+///Creates new `OR` filter out of two filters
+///
+///Evaluation order is determined by [cost_hint](Filter::cost_hint) of both filters at this point.
+pub fn or<F1: Filter, F2: Filter>(left: F1, right: F2) -> Or<F1, F2> {
+    let (cost, is_right_first) = evaluation_order(&left, &right);
+    Or {
+        left,
+        right,
+        cost,
+        is_right_first,
+    }
+}
+
+#[inline]
+///Creates new `AND` filter out of two filters
+///
+///Evaluation order is determined by [cost_hint](Filter::cost_hint) of both filters at this point.
+pub fn and<F1: Filter, F2: Filter>(left: F1, right: F2) -> And<F1, F2> {
+    let (cost, is_right_first) = evaluation_order(&left, &right);
+    And {
+        left,
+        right,
+        cost,
+        is_right_first,
+    }
+}
+
+#[inline]
+///Creates new `NOT` filter, inverting `filter`
+pub const fn not<F>(filter: F) -> Not<F> {
+    Not {
+        filter,
+    }
+}
+
+#[inline]
+///Creates new filter out of function `f`, matching IP when `f` returns `true`
+pub const fn from_fn<F: Fn(IpAddr) -> bool>(f: F) -> FnFilter<F> {
+    FnFilter(f)
+}
+
+#[inline]
+///Creates new filter, matching `filter` unless IP also matches `exception`
+pub const fn except<F, E>(filter: F, exception: E) -> Except<F, E> {
+    Except {
+        filter,
+        exception,
+    }
+}
 
 #[inline]
 ///Creates new `OR` filter out of the `collection`
 pub const fn collection_or<F: Filter, I: AsRef<[F]>>(collection: I) -> CollectionOr<I, F> {
     CollectionOr::new(collection)
 }
+
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+///Type erased filter, which can be cheaply cloned
+///
+///Unlike `Box<dyn Filter>` it satisfies `Clone` bound, so filter selected at runtime (e.g. from configuration file) can be stored within application state and used by extractors.
+pub struct DynFilter(alloc::sync::Arc<dyn Filter + Send + Sync>);
+
+#[cfg(feature = "alloc")]
+impl DynFilter {
+    #[inline]
+    ///Creates new instance, erasing type of `filter`
+    pub fn new<F: Filter + Send + Sync + 'static>(filter: F) -> Self {
+        Self(alloc::sync::Arc::new(filter))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<alloc::boxed::Box<dyn Filter + Send + Sync>> for DynFilter {
+    #[inline(always)]
+    fn from(filter: alloc::boxed::Box<dyn Filter + Send + Sync>) -> Self {
+        Self(filter.into())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for DynFilter {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("DynFilter")
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Filter for DynFilter {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.0.is_match(ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        self.0.cost_hint()
+    }
+}
+
+#[cfg(feature = "std")]
+///Filter shared between multiple owners, which can be replaced at runtime
+///
+///Clones refer to the same filter, hence replacing it via one clone affects all of them.
+///This allows to refresh filter (e.g. trusted CIDRs received from control plane) without restarting server.
+///
+///Filter is protected by `RwLock`, so matching blocks only while filter is being replaced.
+pub struct SharedFilter<F> {
+    filter: alloc::sync::Arc<std::sync::RwLock<F>>,
+}
+
+#[cfg(feature = "std")]
+impl<F> SharedFilter<F> {
+    #[inline]
+    ///Creates new instance
+    pub fn new(filter: F) -> Self {
+        Self {
+            filter: alloc::sync::Arc::new(std::sync::RwLock::new(filter)),
+        }
+    }
+
+    ///Replaces filter, returning previous one
+    pub fn replace(&self, filter: F) -> F {
+        let mut current = self.filter.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+        core::mem::replace(&mut *current, filter)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F> Clone for SharedFilter<F> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            filter: self.filter.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F> fmt::Debug for SharedFilter<F> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("SharedFilter")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<F: Filter> Filter for SharedFilter<F> {
+    #[inline]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.filter.read().unwrap_or_else(std::sync::PoisonError::into_inner).is_match(ip)
+    }
+
+    #[inline]
+    fn cost_hint(&self) -> u32 {
+        self.filter.read().unwrap_or_else(std::sync::PoisonError::into_inner).cost_hint().saturating_add(1)
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Default, Debug)]
+struct Counters {
+    matched: core::sync::atomic::AtomicUsize,
+    passed: core::sync::atomic::AtomicUsize,
+}
+
+#[cfg(feature = "alloc")]
+///Filter wrapper, counting how many addresses matched inner filter and how many passed through
+///
+///Clones share the same counters.
+///Sudden growth of passed addresses usually indicates that list of trusted proxies is stale.
+pub struct Counted<F> {
+    filter: F,
+    counters: alloc::sync::Arc<Counters>,
+}
+
+#[cfg(feature = "alloc")]
+impl<F> Counted<F> {
+    #[inline]
+    ///Creates new instance with zero counters
+    pub fn new(filter: F) -> Self {
+        Self {
+            filter,
+            counters: alloc::sync::Arc::default(),
+        }
+    }
+
+    #[inline(always)]
+    ///Returns number of addresses that matched inner filter
+    pub fn matched(&self) -> usize {
+        self.counters.matched.load(core::sync::atomic::Ordering::Relaxed)
+    }
+
+    #[inline(always)]
+    ///Returns number of addresses that did not match inner filter
+    pub fn passed(&self) -> usize {
+        self.counters.passed.load(core::sync::atomic::Ordering::Relaxed)
+    }
+
+    #[inline]
+    ///Resets both counters to zero
+    pub fn reset(&self) {
+        self.counters.matched.store(0, core::sync::atomic::Ordering::Relaxed);
+        self.counters.passed.store(0, core::sync::atomic::Ordering::Relaxed);
+    }
+
+    #[inline(always)]
+    ///Access inner filter
+    pub fn inner(&self) -> &F {
+        &self.filter
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F: Clone> Clone for Counted<F> {
+    #[inline(always)]
+    fn clone(&self) -> Self {
+        Self {
+            filter: self.filter.clone(),
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F> fmt::Debug for Counted<F> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Counted").field("matched", &self.matched()).field("passed", &self.passed()).finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<F: Filter> Filter for Counted<F> {
+    #[inline]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        let is_match = self.filter.is_match(ip);
+        let counter = if is_match {
+            &self.counters.matched
+        } else {
+            &self.counters.passed
+        };
+        counter.fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+        is_match
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        self.filter.cost_hint().saturating_add(1)
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+///List of arbitrary filters, combined with `OR` condition
+///
+///Filters are stored as trait objects, allowing to build filter at runtime (e.g. from configuration)
+pub struct FilterList {
+    filters: alloc::vec::Vec<alloc::boxed::Box<dyn Filter + Send + Sync>>,
+}
+
+#[cfg(feature = "alloc")]
+impl FilterList {
+    #[inline(always)]
+    ///Creates new empty list, which never matches
+    pub const fn new() -> Self {
+        Self {
+            filters: alloc::vec::Vec::new(),
+        }
+    }
+
+    #[inline]
+    ///Adds `filter` to the list
+    pub fn push<F: Filter + Send + Sync + 'static>(&mut self, filter: F) -> &mut Self {
+        self.push_boxed(alloc::boxed::Box::new(filter))
+    }
+
+    #[inline]
+    ///Adds already boxed `filter` to the list
+    pub fn push_boxed(&mut self, filter: alloc::boxed::Box<dyn Filter + Send + Sync>) -> &mut Self {
+        self.filters.push(filter);
+        self
+    }
+
+    #[inline(always)]
+    ///Returns number of filters in the list
+    pub fn len(&self) -> usize {
+        self.filters.len()
+    }
+
+    #[inline(always)]
+    ///Returns whether list is empty
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Filter for FilterList {
+    #[inline]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.filters.iter().any(|filter| filter.is_match(ip))
+    }
+
+    #[inline]
+    fn cost_hint(&self) -> u32 {
+        self.filters.iter().fold(0, |cost, filter| cost.saturating_add(filter.cost_hint()))
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+///Runtime sized collection of filters, combined with `OR` condition
+///
+///Created by [any]
+pub struct AnyOf<F> {
+    filters: alloc::vec::Vec<F>,
+}
+
+#[cfg(feature = "alloc")]
+impl<F: Filter> Filter for AnyOf<F> {
+    #[inline]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.filters.iter().any(|filter| filter.is_match(ip))
+    }
+
+    #[inline]
+    fn cost_hint(&self) -> u32 {
+        self.filters.as_slice().cost_hint()
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+///Runtime sized collection of filters, combined with `AND` condition
+///
+///Created by [all]
+pub struct AllOf<F> {
+    filters: alloc::vec::Vec<F>,
+}
+
+#[cfg(feature = "alloc")]
+impl<F: Filter> Filter for AllOf<F> {
+    #[inline]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.filters.iter().all(|filter| filter.is_match(ip))
+    }
+
+    #[inline]
+    fn cost_hint(&self) -> u32 {
+        self.filters.as_slice().cost_hint()
+    }
+}
+
+#[cfg(feature = "alloc")]
+//Collects filters, placing cheaper filters first
+fn collect_by_cost<F: Filter>(filters: impl IntoIterator<Item = F>) -> alloc::vec::Vec<F> {
+    let mut filters: alloc::vec::Vec<F> = filters.into_iter().collect();
+    filters.sort_by_key(|filter| filter.cost_hint());
+    filters
+}
+
+#[cfg(feature = "alloc")]
+#[inline]
+///Creates `OR` filter out of runtime sized sequence of `filters`
+///
+///Filters are evaluated starting from the cheapest. Empty sequence never matches.
+pub fn any<F: Filter>(filters: impl IntoIterator<Item = F>) -> AnyOf<F> {
+    AnyOf {
+        filters: collect_by_cost(filters),
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[inline]
+///Creates `AND` filter out of runtime sized sequence of `filters`
+///
+///Filters are evaluated starting from the cheapest. Empty sequence always matches.
+pub fn all<F: Filter>(filters: impl IntoIterator<Item = F>) -> AllOf<F> {
+    AllOf {
+        filters: collect_by_cost(filters),
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Copy, Clone, Default, Debug)]
+struct TrieNode {
+    //Indexes of child nodes for bit 0 and 1, where 0 means no child as root cannot be child
+    children: [u32; 2],
+    //Indicates that CIDR block ends at this node
+    is_terminal: bool,
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+///Prefix trie of CIDR blocks, matching if IP is within any of the blocks
+///
+///Lookup takes at most as many steps as there are bits in IP address, regardless of number of CIDR blocks,
+///making it suitable to filter against large lists of trusted networks
+pub struct CidrTrie {
+    //First node is root of IPv4 blocks, second node is root of IPv6 blocks
+    nodes: alloc::vec::Vec<TrieNode>,
+}
+
+#[cfg(feature = "alloc")]
+impl CidrTrie {
+    const IPV4_ROOT: usize = 0;
+    const IPV6_ROOT: usize = 1;
+
+    #[inline]
+    ///Creates new empty instance
+    pub fn new() -> Self {
+        Self {
+            nodes: alloc::vec![TrieNode::default(); 2],
+        }
+    }
+
+    #[inline]
+    ///Creates new instance out of provided CIDR blocks
+    pub fn from_cidrs(cidrs: &[Cidr]) -> Self {
+        cidrs.iter().copied().collect()
+    }
+
+    #[inline]
+    ///Creates new instance out of line oriented list of IPs and CIDR blocks
+    ///
+    ///Format is described in [parse_cidr_lines], first invalid entry is returned as error
+    pub fn from_text_lines(text: &str) -> Result<Self, CidrParseError<'_>> {
+        parse_cidr_lines(text).collect()
+    }
+
+    #[inline(always)]
+    //Returns root and address bits aligned to the most significant bit
+    fn root_of(ip: IpAddr) -> (usize, u128, u8) {
+        match ip {
+            IpAddr::V4(ip) => (Self::IPV4_ROOT, (ip.to_bits() as u128) << (u128::BITS - u32::BITS), u32::BITS as u8),
+            IpAddr::V6(ip) => (Self::IPV6_ROOT, ip.to_bits(), u128::BITS as u8),
+        }
+    }
+
+    ///Adds CIDR block
+    pub fn insert(&mut self, cidr: Cidr) {
+        let (mut node, bits, _) = Self::root_of(cidr.network_addr());
+        for idx in 0..cidr.prefix() {
+            if self.nodes[node].is_terminal {
+                //Block is already covered by broader block
+                return;
+            }
+
+            let bit = ((bits >> (u128::BITS - 1 - idx as u32)) & 1) as usize;
+            node = match self.nodes[node].children[bit] {
+                0 => {
+                    let child = self.nodes.len();
+                    self.nodes.push(TrieNode::default());
+                    self.nodes[node].children[bit] = child as u32;
+                    child
+                },
+                child => child as usize,
+            };
+        }
+
+        self.nodes[node].is_terminal = true;
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Extend<Cidr> for CidrTrie {
+    #[inline]
+    fn extend<I: IntoIterator<Item = Cidr>>(&mut self, cidrs: I) {
+        for cidr in cidrs {
+            self.insert(cidr);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromIterator<Cidr> for CidrTrie {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = Cidr>>(cidrs: I) -> Self {
+        let mut result = Self::new();
+        result.extend(cidrs);
+        result
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Default for CidrTrie {
+    #[inline(always)]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Filter for CidrTrie {
+    fn is_match(&self, ip: IpAddr) -> bool {
+        let (mut node, bits, len) = Self::root_of(ip);
+        for idx in 0..len {
+            if self.nodes[node].is_terminal {
+                return true;
+            }
+
+            let bit = ((bits >> (u128::BITS - 1 - idx as u32)) & 1) as usize;
+            node = match self.nodes[node].children[bit] {
+                0 => return false,
+                child => child as usize,
+            };
+        }
+
+        self.nodes[node].is_terminal
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        //Walk over trie is proportional to prefix length
+        16
+    }
+}
+
+///Parses line oriented list of IPs and CIDR blocks, as used by `nginx` or `HAProxy` ACL files
+///
+///Each line contains single IP or CIDR block, parsed as in [Cidr::from_text].
+///Everything after `#` is treated as comment, while empty lines and surrounding whitespace are ignored.
+pub fn parse_cidr_lines(text: &str) -> impl Iterator<Item = Result<Cidr, CidrParseError<'_>>> {
+    text.lines().map(|line| match line.split_once('#') {
+        Some((line, _)) => line.trim(),
+        None => line.trim(),
+    }).filter(|line| !line.is_empty()).map(Cidr::from_text)
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Default)]
+///Builder of trusted proxies filter, combining presets, CIDR blocks, exact IPs and custom filters
+///
+///CIDR blocks and IPs are stored within single [CidrTrie], while custom filters are evaluated only when trie doesn't match.
+///
+///```rust
+///use http_ip::filter::{Cidr, Filter, TrustedProxies};
+///
+///let trusted = TrustedProxies::new().private_networks().loopback().cidr(Cidr::from_text("198.51.100.0/24").unwrap()).ip("203.0.113.7".parse().unwrap());
+///assert!(trusted.is_match("10.0.0.1".parse().unwrap()));
+///assert!(trusted.is_match("203.0.113.7".parse().unwrap()));
+///assert!(!trusted.is_match("203.0.113.8".parse().unwrap()));
+///```
+pub struct TrustedProxies {
+    cidrs: CidrTrie,
+    filters: FilterList,
+}
+
+#[cfg(feature = "alloc")]
+impl TrustedProxies {
+    #[inline(always)]
+    ///Creates new empty instance, which never matches
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    ///Adds CIDR block
+    pub fn cidr(mut self, cidr: Cidr) -> Self {
+        self.cidrs.insert(cidr);
+        self
+    }
+
+    #[inline]
+    ///Adds CIDR blocks
+    pub fn cidrs(mut self, cidrs: impl IntoIterator<Item = Cidr>) -> Self {
+        self.cidrs.extend(cidrs);
+        self
+    }
+
+    #[inline]
+    ///Adds exact IP
+    pub fn ip(self, ip: IpAddr) -> Self {
+        let prefix = match ip {
+            IpAddr::V4(_) => u32::BITS,
+            IpAddr::V6(_) => u128::BITS,
+        };
+        match Cidr::new(ip, prefix as u8) {
+            Ok(cidr) => self.cidr(cidr),
+            //Full length prefix is always valid
+            Err(_) => self,
+        }
+    }
+
+    #[inline]
+    ///Adds all CIDR blocks of `preset` (e.g. [cloud::CLOUDFLARE])
+    pub fn preset<I: AsRef<[Cidr]>>(self, preset: CidrSet<I>) -> Self {
+        self.cidrs(preset.cidrs().iter().copied())
+    }
+
+    #[inline(always)]
+    ///Adds [PRIVATE_NETWORKS]
+    pub fn private_networks(self) -> Self {
+        self.preset(PRIVATE_NETWORKS)
+    }
+
+    #[inline(always)]
+    ///Adds [LOOPBACK]
+    pub fn loopback(self) -> Self {
+        self.preset(LOOPBACK)
+    }
+
+    #[inline(always)]
+    ///Adds [LINK_LOCAL]
+    pub fn link_local(self) -> Self {
+        self.preset(LINK_LOCAL)
+    }
+
+    #[inline(always)]
+    ///Adds [NON_PUBLIC]
+    pub fn non_public(self) -> Self {
+        self.preset(NON_PUBLIC)
+    }
+
+    #[inline]
+    ///Adds custom `filter`
+    pub fn filter<F: Filter + Send + Sync + 'static>(mut self, filter: F) -> Self {
+        self.filters.push(filter);
+        self
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Filter for TrustedProxies {
+    #[inline]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.cidrs.is_match(ip) || self.filters.is_match(ip)
+    }
+
+    #[inline]
+    fn cost_hint(&self) -> u32 {
+        self.cidrs.cost_hint().saturating_add(self.filters.cost_hint())
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug)]
+///Probabilistic set of IPs, suitable for very large denylists
+///
+///It never misses inserted IP, but may match IP that was not inserted with configured false positive rate.
+///Memory usage is about `1.44 * log2(1 / false_positive_rate)` bits per expected IP, regardless of IP family.
+pub struct BloomFilter {
+    bits: alloc::vec::Vec<u64>,
+    bits_len: u64,
+    hashes: u32,
+}
+
+#[cfg(feature = "alloc")]
+impl BloomFilter {
+    ///Creates new empty filter, sized for `expected_len` IPs with desired `false_positive_rate`
+    ///
+    ///`false_positive_rate` is clamped to `(0, 0.5]` range
+    pub fn new(expected_len: usize, false_positive_rate: f64) -> Self {
+        //Optimal number of hashes is log2(1 / rate), while number of bits per item is hashes / ln(2)
+        let mut hashes = 1u32;
+        let mut rate = 0.5;
+        while rate > false_positive_rate && hashes < 32 {
+            rate /= 2.0;
+            hashes += 1;
+        }
+
+        let expected_len = expected_len.max(1) as u64;
+        let bits_len = expected_len.saturating_mul(hashes as u64).saturating_mul(14427).div_ceil(10000).max(64);
+        Self {
+            bits: alloc::vec![0; bits_len.div_ceil(64) as usize],
+            bits_len,
+            hashes,
+        }
+    }
+
+    ///Creates new filter out of `ips`, sized according to number of IPs
+    pub fn from_ips<I: IntoIterator<Item = IpAddr>>(ips: I, false_positive_rate: f64) -> Self where I::IntoIter: ExactSizeIterator {
+        let ips = ips.into_iter();
+        let mut result = Self::new(ips.len(), false_positive_rate);
+        result.extend(ips);
+        result
+    }
+
+    #[inline]
+    //Double hashing: i-th bit index is `first + i * second`
+    fn hash(ip: IpAddr) -> (u64, u64) {
+        //splitmix64 finalizer
+        const fn mix(mut value: u64) -> u64 {
+            value = (value ^ (value >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            value = (value ^ (value >> 27)).wrapping_mul(0x94d049bb133111eb);
+            value ^ (value >> 31)
+        }
+
+        let bits = match ip {
+            IpAddr::V4(ip) => ip.to_bits() as u128 | (1 << 64),
+            IpAddr::V6(ip) => ip.to_bits(),
+        };
+        let first = mix((bits as u64) ^ mix((bits >> 64) as u64));
+        let second = mix(first ^ 0x9e3779b97f4a7c15) | 1;
+        (first, second)
+    }
+
+    #[inline]
+    fn bit_indexes(hashes: u32, bits_len: u64, ip: IpAddr) -> impl Iterator<Item = u64> {
+        let (first, second) = Self::hash(ip);
+        (0..hashes as u64).map(move |idx| first.wrapping_add(idx.wrapping_mul(second)) % bits_len)
+    }
+
+    ///Adds `ip` to the set
+    pub fn insert(&mut self, ip: IpAddr) {
+        for bit in Self::bit_indexes(self.hashes, self.bits_len, ip) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Extend<IpAddr> for BloomFilter {
+    #[inline]
+    fn extend<I: IntoIterator<Item = IpAddr>>(&mut self, ips: I) {
+        for ip in ips {
+            self.insert(ip);
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Filter for BloomFilter {
+    #[inline]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        Self::bit_indexes(self.hashes, self.bits_len, ip).all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        self.hashes
+    }
+}
+
+#[cfg(feature = "alloc")]
+///Removes duplicates and CIDR blocks fully contained within another block of `cidrs`
+///
+///Order of remaining blocks is preserved.
+pub fn dedup_cidrs(cidrs: &[Cidr]) -> alloc::vec::Vec<Cidr> {
+    let mut result = alloc::vec::Vec::with_capacity(cidrs.len());
+    for (idx, cidr) in cidrs.iter().enumerate() {
+        let is_redundant = cidrs.iter().enumerate().any(|(other_idx, other)| {
+            other_idx != idx && other.contains_cidr(cidr) && (other_idx < idx || !cidr.contains_cidr(other))
+        });
+
+        if !is_redundant {
+            result.push(*cidr);
+        }
+    }
+
+    result
+}
+
+#[cfg(feature = "serde")]
+//Stack buffer to format CIDR without allocation
+struct CidrTextBuffer {
+    buffer: [u8; 64],
+    len: usize,
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Write for CidrTextBuffer {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        let end = self.len + text.len();
+        match self.buffer.get_mut(self.len..end) {
+            Some(dest) => {
+                dest.copy_from_slice(text.as_bytes());
+                self.len = end;
+                Ok(())
+            },
+            None => Err(fmt::Error),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Cidr {
+    ///Serializes CIDR as its textual form
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use fmt::Write;
+        use serde::ser::Error;
+
+        let mut text = CidrTextBuffer {
+            buffer: [0; 64],
+            len: 0,
+        };
+        if write!(&mut text, "{self}").is_err() {
+            return Err(S::Error::custom("CIDR text is too long"));
+        }
+        match core::str::from_utf8(&text.buffer[..text.len]) {
+            Ok(text) => serializer.serialize_str(text),
+            Err(_) => Err(S::Error::custom("CIDR text is not valid UTF-8")),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Cidr {
+    ///Deserializes CIDR from its textual form, as in [Cidr::from_text]
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct CidrVisitor;
+
+        impl serde::de::Visitor<'_> for CidrVisitor {
+            type Value = Cidr;
+
+            #[inline(always)]
+            fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt.write_str("CIDR string")
+            }
+
+            #[inline]
+            fn visit_str<E: serde::de::Error>(self, text: &str) -> Result<Self::Value, E> {
+                Cidr::from_text(text).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(CidrVisitor)
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "alloc"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+///Filter configuration, which can be deserialized from configuration files
+///
+///It is deserialized either from string, which is single IP or CIDR, or from list of configurations (e.g. `["10.0.0.1", "192.168.0.0/16"]`)
+pub enum FilterConfig {
+    ///Single IP address
+    Ip(IpAddr),
+    ///CIDR block
+    Cidr(Cidr),
+    ///List of configurations, combined with `OR` condition
+    List(alloc::vec::Vec<FilterConfig>),
+}
+
+#[cfg(all(feature = "serde", feature = "alloc"))]
+impl FilterConfig {
+    fn collect_cidrs(self, trie: &mut CidrTrie) {
+        match self {
+            Self::Ip(ip) => {
+                let prefix = match ip {
+                    IpAddr::V4(_) => u32::BITS,
+                    IpAddr::V6(_) => u128::BITS,
+                };
+                if let Ok(cidr) = Cidr::new(ip, prefix as u8) {
+                    trie.insert(cidr);
+                }
+            },
+            Self::Cidr(cidr) => trie.insert(cidr),
+            Self::List(list) => for config in list {
+                config.collect_cidrs(trie);
+            },
+        }
+    }
+
+    ///Converts configuration into runtime filter
+    ///
+    ///Lists are flattened into single [CidrTrie]
+    pub fn into_filter(self) -> DynFilter {
+        match self {
+            Self::Ip(ip) => DynFilter::new(ip),
+            Self::Cidr(cidr) => DynFilter::new(cidr),
+            Self::List(_) => {
+                let mut trie = CidrTrie::new();
+                self.collect_cidrs(&mut trie);
+                DynFilter::new(trie)
+            },
+        }
+    }
+}
+
+#[cfg(all(feature = "serde", feature = "alloc"))]
+impl<'de> serde::Deserialize<'de> for FilterConfig {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FilterConfigVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for FilterConfigVisitor {
+            type Value = FilterConfig;
+
+            #[inline(always)]
+            fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt.write_str("IP, CIDR string or list of them")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, text: &str) -> Result<Self::Value, E> {
+                match text.parse() {
+                    Ok(ip) => Ok(FilterConfig::Ip(ip)),
+                    Err(_) => Cidr::from_text(text).map(FilterConfig::Cidr).map_err(E::custom),
+                }
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let mut list = alloc::vec::Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                while let Some(config) = seq.next_element()? {
+                    list.push(config);
+                }
+                Ok(FilterConfig::List(list))
+            }
+        }
+
+        deserializer.deserialize_any(FilterConfigVisitor)
+    }
+}
+
+#[cfg(feature = "ipnet")]
+impl Filter for ipnet::IpNet {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.contains(&ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        2
+    }
+}
+
+#[cfg(feature = "ipnet")]
+impl Filter for ipnet::Ipv4Net {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        match ip {
+            IpAddr::V4(ip) => self.contains(&ip),
+            IpAddr::V6(_) => false,
+        }
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        2
+    }
+}
+
+#[cfg(feature = "ipnet")]
+impl Filter for ipnet::Ipv6Net {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        match ip {
+            IpAddr::V4(_) => false,
+            IpAddr::V6(ip) => self.contains(&ip),
+        }
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        2
+    }
+}
+
+#[cfg(feature = "ipnet")]
+impl From<ipnet::IpNet> for Cidr {
+    #[inline]
+    ///Converts network, discarding host bits
+    fn from(net: ipnet::IpNet) -> Self {
+        match net {
+            ipnet::IpNet::V4(net) => net.into(),
+            ipnet::IpNet::V6(net) => net.into(),
+        }
+    }
+}
+
+#[cfg(feature = "ipnet")]
+impl From<ipnet::Ipv4Net> for Cidr {
+    #[inline]
+    ///Converts network, discarding host bits
+    fn from(net: ipnet::Ipv4Net) -> Self {
+        match Self::new(IpAddr::V4(net.network()), net.prefix_len()) {
+            Ok(cidr) => cidr,
+            //ipnet guarantees valid prefix
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+#[cfg(feature = "ipnet")]
+impl From<ipnet::Ipv6Net> for Cidr {
+    #[inline]
+    ///Converts network, discarding host bits
+    fn from(net: ipnet::Ipv6Net) -> Self {
+        match Self::new(IpAddr::V6(net.network()), net.prefix_len()) {
+            Ok(cidr) => cidr,
+            //ipnet guarantees valid prefix
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+#[cfg(feature = "ipnet")]
+impl From<Cidr> for ipnet::IpNet {
+    #[inline]
+    fn from(cidr: Cidr) -> Self {
+        match cidr.network_addr() {
+            IpAddr::V4(ip) => ipnet::IpNet::V4(ipnet::Ipv4Net::new_assert(ip, cidr.prefix())),
+            IpAddr::V6(ip) => ipnet::IpNet::V6(ipnet::Ipv6Net::new_assert(ip, cidr.prefix())),
+        }
+    }
+}
+
+#[cfg(feature = "cidr")]
+impl Filter for cidr::IpCidr {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.contains(&ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        2
+    }
+}
+
+#[cfg(feature = "cidr")]
+impl Filter for cidr::Ipv4Cidr {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        match ip {
+            IpAddr::V4(ip) => self.contains(&ip),
+            IpAddr::V6(_) => false,
+        }
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        2
+    }
+}
+
+#[cfg(feature = "cidr")]
+impl Filter for cidr::Ipv6Cidr {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        match ip {
+            IpAddr::V4(_) => false,
+            IpAddr::V6(ip) => self.contains(&ip),
+        }
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        2
+    }
+}
+
+#[cfg(feature = "cidr")]
+impl From<cidr::IpCidr> for Cidr {
+    #[inline]
+    fn from(net: cidr::IpCidr) -> Self {
+        match net {
+            cidr::IpCidr::V4(net) => net.into(),
+            cidr::IpCidr::V6(net) => net.into(),
+        }
+    }
+}
+
+#[cfg(feature = "cidr")]
+impl From<cidr::Ipv4Cidr> for Cidr {
+    #[inline]
+    fn from(net: cidr::Ipv4Cidr) -> Self {
+        match Self::new(IpAddr::V4(net.first_address()), net.network_length()) {
+            Ok(cidr) => cidr,
+            //cidr guarantees valid prefix
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+#[cfg(feature = "cidr")]
+impl From<cidr::Ipv6Cidr> for Cidr {
+    #[inline]
+    fn from(net: cidr::Ipv6Cidr) -> Self {
+        match Self::new(IpAddr::V6(net.first_address()), net.network_length()) {
+            Ok(cidr) => cidr,
+            //cidr guarantees valid prefix
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+#[cfg(feature = "cidr")]
+impl From<Cidr> for cidr::IpCidr {
+    #[inline]
+    fn from(cidr: Cidr) -> Self {
+        match Self::new(cidr.network_addr(), cidr.prefix()) {
+            Ok(net) => net,
+            //Cidr always stores network address with valid prefix
+            Err(_) => unreachable!(),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//!Filtering by autonomous system number (ASN)
+//!
+//!Crate doesn't bundle any routing data: table of announced prefixes (e.g. from CAIDA's pfx2as or MRT dump) is provided by the caller.
+
+use core::net::IpAddr;
+
+use super::{Cidr, CidrTrie, Filter};
+
+#[derive(Clone, Debug, Default)]
+///Filter matching any address announced by one of the configured ASNs
+pub struct AsnFilter {
+    prefixes: CidrTrie,
+}
+
+impl AsnFilter {
+    ///Creates new filter out of prefix to origin ASN `table`, keeping only prefixes announced by one of `asns`
+    ///
+    ///Table may contain the same prefix multiple times if it is announced by multiple ASNs.
+    pub fn from_table(table: impl IntoIterator<Item = (Cidr, u32)>, asns: &[u32]) -> Self {
+        Self {
+            prefixes: table.into_iter().filter(|(_, asn)| asns.contains(asn)).map(|(cidr, _)| cidr).collect(),
+        }
+    }
+}
+
+impl Filter for AsnFilter {
+    #[inline(always)]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        self.prefixes.is_match(ip)
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        self.prefixes.cost_hint()
+    }
+}
@@ -0,0 +1,245 @@
+//!Filters for well known cloud providers
+//!
+//!Providers publish their ranges and change them from time to time, hence bundled data may become outdated.
+//!Each provider has a parser for its published format, which allows to refresh ranges at runtime.
+//!
+//!Parsers of JSON formats require `cloud-ranges` feature.
+
+use super::{Cidr, CidrParseError, CidrSet};
+
+const CLOUDFLARE_CIDRS: [Cidr; 22] = crate::cidrs![
+    //https://www.cloudflare.com/ips-v4
+    "173.245.48.0/20",
+    "103.21.244.0/22",
+    "103.22.200.0/22",
+    "103.31.4.0/22",
+    "141.101.64.0/18",
+    "108.162.192.0/18",
+    "190.93.240.0/20",
+    "188.114.96.0/20",
+    "197.234.240.0/22",
+    "198.41.128.0/17",
+    "162.158.0.0/15",
+    "104.16.0.0/13",
+    "104.24.0.0/14",
+    "172.64.0.0/13",
+    "131.0.72.0/22",
+    //https://www.cloudflare.com/ips-v6
+    "2400:cb00::/32",
+    "2606:4700::/32",
+    "2803:f800::/32",
+    "2405:b500::/32",
+    "2405:8100::/32",
+    "2a06:98c0::/29",
+    "2c0f:f248::/32",
+];
+
+///Cloudflare's IPv4 and IPv6 ranges
+///
+///Data corresponds to <https://www.cloudflare.com/ips/> as published in 2024.
+///Use [parse_cloudflare_ips] to load up to date list.
+pub const CLOUDFLARE: CidrSet<&[Cidr]> = CidrSet::new(&CLOUDFLARE_CIDRS);
+
+#[inline]
+///Parses Cloudflare's `ips-v4`/`ips-v6` text format, which has one CIDR per line
+///
+///Empty lines and surrounding whitespace are ignored.
+pub fn parse_cloudflare_ips(text: &str) -> impl Iterator<Item = Result<Cidr, CidrParseError<'_>>> {
+    super::parse_cidr_lines(text)
+}
+
+#[cfg(feature = "cloud-ranges")]
+#[derive(Clone, Debug, serde::Deserialize)]
+///Entry of AWS's `ip-ranges.json`
+pub struct AwsIpPrefix {
+    #[serde(rename = "ip_prefix", alias = "ipv6_prefix")]
+    ///CIDR block
+    pub cidr: Cidr,
+    ///AWS region (e.g. `us-east-1` or `GLOBAL`)
+    pub region: alloc::string::String,
+    ///AWS service (e.g. `CLOUDFRONT` or `AMAZON`)
+    pub service: alloc::string::String,
+}
+
+#[cfg(feature = "cloud-ranges")]
+#[derive(Clone, Debug, Default)]
+///AWS's IP ranges, published at <https://ip-ranges.amazonaws.com/ip-ranges.json>
+pub struct AwsIpRanges {
+    prefixes: alloc::vec::Vec<AwsIpPrefix>,
+}
+
+#[cfg(feature = "cloud-ranges")]
+impl AwsIpRanges {
+    ///Parses content of `ip-ranges.json`, including both `prefixes` and `ipv6_prefixes`
+    pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
+        #[derive(serde::Deserialize)]
+        struct Content {
+            #[serde(default)]
+            prefixes: alloc::vec::Vec<AwsIpPrefix>,
+            #[serde(default)]
+            ipv6_prefixes: alloc::vec::Vec<AwsIpPrefix>,
+        }
+
+        let Content { mut prefixes, ipv6_prefixes } = serde_json::from_str(json)?;
+        prefixes.extend(ipv6_prefixes);
+        Ok(Self {
+            prefixes
+        })
+    }
+
+    #[inline(always)]
+    ///Returns all parsed entries
+    pub fn prefixes(&self) -> &[AwsIpPrefix] {
+        &self.prefixes
+    }
+
+    ///Returns CIDR blocks, optionally limited to specified `service` and `region`
+    ///
+    ///Both `service` and `region` are compared exactly, as written in `ip-ranges.json` (e.g. `CLOUDFRONT`)
+    pub fn cidrs<'a>(&'a self, service: Option<&'a str>, region: Option<&'a str>) -> impl Iterator<Item = Cidr> + 'a {
+        self.prefixes.iter().filter(move |prefix| {
+            service.is_none_or(|service| prefix.service == service) && region.is_none_or(|region| prefix.region == region)
+        }).map(|prefix| prefix.cidr)
+    }
+
+    #[inline]
+    ///Builds trie out of CIDR blocks, optionally limited to specified `service` and `region`
+    pub fn to_trie(&self, service: Option<&str>, region: Option<&str>) -> super::CidrTrie {
+        self.cidrs(service, region).collect()
+    }
+}
+
+#[cfg(feature = "cloud-ranges")]
+#[derive(Clone, Debug, serde::Deserialize)]
+///Entry of Google's `cloud.json` or `goog.json`
+pub struct GcpIpPrefix {
+    #[serde(rename = "ipv4Prefix", alias = "ipv6Prefix")]
+    ///CIDR block
+    pub cidr: Cidr,
+    #[serde(default)]
+    ///Service (e.g. `Google Cloud`), only present in `cloud.json`
+    pub service: Option<alloc::string::String>,
+    #[serde(default)]
+    ///Scope, which is usually region (e.g. `us-central1`), only present in `cloud.json`
+    pub scope: Option<alloc::string::String>,
+}
+
+#[cfg(feature = "cloud-ranges")]
+#[derive(Clone, Debug, Default)]
+///Google's IP ranges, published at <https://www.gstatic.com/ipranges/cloud.json> (customers' cloud resources)
+///and <https://www.gstatic.com/ipranges/goog.json> (Google's own services)
+pub struct GcpIpRanges {
+    prefixes: alloc::vec::Vec<GcpIpPrefix>,
+}
+
+#[cfg(feature = "cloud-ranges")]
+impl GcpIpRanges {
+    ///Parses content of `cloud.json` or `goog.json`
+    pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
+        #[derive(serde::Deserialize)]
+        struct Content {
+            #[serde(default)]
+            prefixes: alloc::vec::Vec<GcpIpPrefix>,
+        }
+
+        let Content { prefixes } = serde_json::from_str(json)?;
+        Ok(Self {
+            prefixes
+        })
+    }
+
+    #[inline(always)]
+    ///Returns all parsed entries
+    pub fn prefixes(&self) -> &[GcpIpPrefix] {
+        &self.prefixes
+    }
+
+    ///Returns CIDR blocks, optionally limited to specified `scope`
+    ///
+    ///Entries without scope never match specified `scope`
+    pub fn cidrs<'a>(&'a self, scope: Option<&'a str>) -> impl Iterator<Item = Cidr> + 'a {
+        self.prefixes.iter().filter(move |prefix| {
+            scope.is_none_or(|scope| prefix.scope.as_deref() == Some(scope))
+        }).map(|prefix| prefix.cidr)
+    }
+
+    #[inline]
+    ///Builds trie out of CIDR blocks, optionally limited to specified `scope`
+    pub fn to_trie(&self, scope: Option<&str>) -> super::CidrTrie {
+        self.cidrs(scope).collect()
+    }
+}
+
+#[cfg(feature = "cloud-ranges")]
+#[derive(Clone, Debug)]
+///Service tag of Azure's `ServiceTags` JSON
+pub struct AzureServiceTag {
+    ///Name of the tag (e.g. `AzureFrontDoor.Backend` or `AzureCloud.westeurope`)
+    pub name: alloc::string::String,
+    ///Region, which is empty for global tags
+    pub region: alloc::string::String,
+    ///CIDR blocks of the tag
+    pub cidrs: alloc::vec::Vec<Cidr>,
+}
+
+#[cfg(feature = "cloud-ranges")]
+#[derive(Clone, Debug, Default)]
+///Azure's IP ranges, published weekly as `ServiceTags_Public_*.json`
+pub struct AzureServiceTags {
+    tags: alloc::vec::Vec<AzureServiceTag>,
+}
+
+#[cfg(feature = "cloud-ranges")]
+impl AzureServiceTags {
+    ///Parses content of `ServiceTags` JSON
+    pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Properties {
+            #[serde(default)]
+            region: alloc::string::String,
+            #[serde(default)]
+            address_prefixes: alloc::vec::Vec<Cidr>,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Value {
+            name: alloc::string::String,
+            properties: Properties,
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Content {
+            #[serde(default)]
+            values: alloc::vec::Vec<Value>,
+        }
+
+        let Content { values } = serde_json::from_str(json)?;
+        Ok(Self {
+            tags: values.into_iter().map(|value| AzureServiceTag {
+                name: value.name,
+                region: value.properties.region,
+                cidrs: value.properties.address_prefixes,
+            }).collect()
+        })
+    }
+
+    #[inline(always)]
+    ///Returns all parsed tags
+    pub fn tags(&self) -> &[AzureServiceTag] {
+        &self.tags
+    }
+
+    ///Returns CIDR blocks, optionally limited to specified tag `name` and `region`
+    pub fn cidrs<'a>(&'a self, name: Option<&'a str>, region: Option<&'a str>) -> impl Iterator<Item = Cidr> + 'a {
+        self.tags.iter().filter(move |tag| {
+            name.is_none_or(|name| tag.name == name) && region.is_none_or(|region| tag.region == region)
+        }).flat_map(|tag| tag.cidrs.iter().copied())
+    }
+
+    #[inline]
+    ///Builds trie out of CIDR blocks, optionally limited to specified tag `name` and `region`
+    pub fn to_trie(&self, name: Option<&str>, region: Option<&str>) -> super::CidrTrie {
+        self.cidrs(name, region).collect()
+    }
+}
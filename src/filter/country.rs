@@ -0,0 +1,98 @@
+//!Filtering by country of IP address
+//!
+//!Crate doesn't bundle geolocation database, instead caller provides [CountryLookup] implementation.
+//!With `maxminddb` feature it is implemented for `maxminddb::Reader`, allowing to use GeoIP2/GeoLite2 database directly.
+
+use core::net::IpAddr;
+
+use super::Filter;
+
+///Interface to look up country of IP address
+pub trait CountryLookup {
+    ///Returns ISO 3166-1 alpha-2 country code (e.g. `*b"DE"`) of `ip`, if known
+    fn country(&self, ip: IpAddr) -> Option<[u8; 2]>;
+}
+
+impl<F: Fn(IpAddr) -> Option<[u8; 2]>> CountryLookup for F {
+    #[inline(always)]
+    fn country(&self, ip: IpAddr) -> Option<[u8; 2]> {
+        (self)(ip)
+    }
+}
+
+///Filter matching IP, when its country is within configured set
+///
+///`I` must be type that implements `AsRef<[[u8; 2]]>`.
+///Country codes are compared case insensitively, while IP with unknown country never matches.
+///
+///## Usage
+///
+///```rust
+///use http_ip::filter::Filter;
+///use http_ip::filter::country::CountryFilter;
+///
+///use core::net::IpAddr;
+///
+///fn lookup(ip: IpAddr) -> Option<[u8; 2]> {
+///    match ip {
+///        IpAddr::V4(ip) if ip.octets()[0] == 198 => Some(*b"DE"),
+///        _ => None,
+///    }
+///}
+///
+///let filter = CountryFilter::new(lookup, [*b"DE", *b"AT"]);
+///assert!(filter.is_match("198.51.100.1".parse().unwrap()));
+///assert!(!filter.is_match("203.0.113.1".parse().unwrap()));
+///```
+pub struct CountryFilter<L, I> {
+    lookup: L,
+    countries: I,
+}
+
+impl<L: CountryLookup, I: AsRef<[[u8; 2]]>> CountryFilter<L, I> {
+    #[inline(always)]
+    ///Creates new filter, matching IPs located within `countries` according to `lookup`
+    pub const fn new(lookup: L, countries: I) -> Self {
+        Self {
+            lookup,
+            countries,
+        }
+    }
+}
+
+impl<L: CountryLookup, I: AsRef<[[u8; 2]]>> Filter for CountryFilter<L, I> {
+    #[inline]
+    fn is_match(&self, ip: IpAddr) -> bool {
+        match self.lookup.country(ip) {
+            Some(country) => self.countries.as_ref().iter().any(|expected| expected.eq_ignore_ascii_case(&country)),
+            None => false,
+        }
+    }
+
+    #[inline(always)]
+    fn cost_hint(&self) -> u32 {
+        //Database lookup is much more expensive than any in-memory filter
+        64
+    }
+}
+
+#[cfg(feature = "maxminddb")]
+///Looks up country within GeoIP2/GeoLite2 Country or City database
+///
+///Country where MaxMind believes IP is located is used, rather than registered country.
+///
+///```rust,no_run
+///use http_ip::filter::Filter;
+///use http_ip::filter::country::CountryFilter;
+///
+///let reader = maxminddb::Reader::open_readfile("GeoLite2-Country.mmdb").expect("to open database");
+///let filter = CountryFilter::new(reader, [*b"DE", *b"AT"]);
+///assert!(filter.is_match("198.51.100.1".parse().unwrap()));
+///```
+impl<S: AsRef<[u8]>> CountryLookup for maxminddb::Reader<S> {
+    #[inline]
+    fn country(&self, ip: IpAddr) -> Option<[u8; 2]> {
+        let country = self.lookup(ip).ok()?.decode::<maxminddb::geoip2::Country<'_>>().ok()??;
+        country.country.iso_code?.as_bytes().try_into().ok()
+    }
+}
@@ -1,7 +1,8 @@
 //! `Forwarded` header module
 
 use core::{marker, fmt};
-use core::net::IpAddr;
+use core::iter::FusedIterator;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 
 //Forwarded syntax
 //Syntax is: <entry 1>, <entry N>
@@ -10,6 +11,267 @@ const FORWARDED_SEP: char = ',';
 const ENTRY_SEP: char = ';';
 const PAIR_SEP: char = '=';
 
+#[inline]
+//Checks whether `value` is `:<port>`
+fn is_port(value: &str) -> bool {
+    match value.strip_prefix(':') {
+        Some(port) => port.parse::<u16>().is_ok(),
+        None => false,
+    }
+}
+
+#[inline]
+//Checks whether `value` is `:<node-port>`, where port can be obfuscated as per RFC 7239
+fn is_node_port(value: &str) -> bool {
+    match value.strip_prefix(':') {
+        Some(port) => match port.strip_prefix('_') {
+            Some(obfport) => !obfport.is_empty() && obfport.bytes().all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, b'.' | b'_' | b'-')),
+            None => port.parse::<u16>().is_ok(),
+        },
+        None => false,
+    }
+}
+
+#[inline]
+//Returns number of components produced by splitting `value` with `seps`
+fn count_components<const N: usize>(value: &str, seps: [char; N]) -> usize {
+    value.bytes().filter(|byte| seps.iter().any(|sep| *sep as u8 == *byte)).count().saturating_add(1)
+}
+
+#[inline]
+//Returns position of quote, closing quoted string started at `start`
+fn find_closing_quote(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut idx = start + 1;
+    while idx < bytes.len() {
+        match bytes[idx] {
+            b'\\' => idx += 1,
+            b'"' => return Some(idx),
+            _ => (),
+        }
+        idx += 1;
+    }
+
+    None
+}
+
+//Returns length of `bytes` prefix, where every quoted string is terminated and opening quote is not preceded by backslash
+//
+//Prefix ends at first unterminated quote, which cannot be followed by another quoted string.
+//It also ends at opening quote after backslash, which would be indistinguishable from escaped quote when going backwards.
+fn quoted_prefix_len(bytes: &[u8]) -> usize {
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'"' {
+            if idx > 0 && bytes[idx - 1] == b'\\' {
+                return idx;
+            }
+
+            match find_closing_quote(bytes, idx) {
+                Some(end) => idx = end,
+                None => return idx,
+            }
+        }
+        idx += 1;
+    }
+
+    bytes.len()
+}
+
+#[derive(Clone)]
+//Splits `value` by any of `seps`, skipping separators within quoted strings
+//
+//Quoted string is as defined by RFC 7230, i.e. enclosed into `"` with `\` escaping following character.
+//Unterminated quote is not considered as start of quoted string, as it is most likely malformed value.
+//
+//Both directions take linear time in total, as quoted strings are located up front.
+struct QuotedSplit<'a, const N: usize> {
+    value: &'a str,
+    seps: [u8; N],
+    //Length of `value` prefix with terminated quoted strings, the rest contains no quoted strings
+    quoted_len: usize,
+    is_finished: bool,
+}
+
+impl<'a, const N: usize> QuotedSplit<'a, N> {
+    #[inline]
+    fn new(value: &'a str, seps: [u8; N]) -> Self {
+        Self {
+            value,
+            seps,
+            quoted_len: quoted_prefix_len(value.as_bytes()),
+            is_finished: false,
+        }
+    }
+
+    #[inline(always)]
+    fn is_sep(&self, byte: u8) -> bool {
+        self.seps.contains(&byte)
+    }
+
+    //Returns position of the first separator outside of quoted string
+    fn find_first(&self) -> Option<usize> {
+        let bytes = self.value.as_bytes();
+        let mut idx = 0;
+        while idx < self.quoted_len {
+            match bytes[idx] {
+                b'"' => if let Some(end) = find_closing_quote(bytes, idx) {
+                    idx = end;
+                },
+                byte => if self.is_sep(byte) {
+                    return Some(idx);
+                },
+            }
+            idx += 1;
+        }
+
+        bytes[idx..].iter().position(|byte| self.is_sep(*byte)).map(|pos| idx + pos)
+    }
+
+    //Returns position of the last separator outside of quoted string
+    fn find_last(&self) -> Option<usize> {
+        let bytes = self.value.as_bytes();
+        if let Some(pos) = bytes[self.quoted_len..].iter().rposition(|byte| self.is_sep(*byte)) {
+            return Some(self.quoted_len + pos);
+        }
+
+        //Every quote outside of quoted string is closing one, when going backwards within prefix.
+        //Quote within quoted string is always escaped, while opening quote is never preceded by backslash within prefix.
+        let bytes = &bytes[..self.quoted_len];
+        let mut idx = bytes.len();
+        while idx > 0 {
+            idx -= 1;
+            match bytes[idx] {
+                b'"' => loop {
+                    idx = bytes[..idx].iter().rposition(|byte| *byte == b'"')?;
+                    if idx == 0 || bytes[idx - 1] != b'\\' {
+                        break;
+                    }
+                },
+                byte => if self.is_sep(byte) {
+                    return Some(idx);
+                },
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a, const N: usize> Iterator for QuotedSplit<'a, N> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+
+        match self.find_first() {
+            Some(idx) => {
+                let result = &self.value[..idx];
+                self.value = &self.value[idx + 1..];
+                self.quoted_len = self.quoted_len.saturating_sub(idx + 1);
+                Some(result)
+            }
+            None => {
+                self.is_finished = true;
+                Some(self.value)
+            }
+        }
+    }
+}
+
+impl<const N: usize> DoubleEndedIterator for QuotedSplit<'_, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.is_finished {
+            return None;
+        }
+
+        match self.find_last() {
+            Some(idx) => {
+                let result = &self.value[idx + 1..];
+                self.value = &self.value[..idx];
+                self.quoted_len = self.quoted_len.min(idx);
+                Some(result)
+            }
+            None => {
+                self.is_finished = true;
+                Some(self.value)
+            }
+        }
+    }
+}
+
+impl<const N: usize> FusedIterator for QuotedSplit<'_, N> {}
+
+//Maximum length of quoted value that can be unescaped
+const UNESCAPE_BUFFER_SIZE: usize = 64;
+
+//Removes quoted-pair escapes from `value`, returning `None` if it doesn't fit `buffer`
+fn unescape<'b>(value: &str, buffer: &'b mut [u8; UNESCAPE_BUFFER_SIZE]) -> Option<&'b str> {
+    let mut len = 0;
+    let mut is_escaped = false;
+    for byte in value.bytes() {
+        if !is_escaped && byte == b'\\' {
+            is_escaped = true;
+            continue;
+        }
+
+        is_escaped = false;
+        *buffer.get_mut(len)? = byte;
+        len += 1;
+    }
+
+    //Only ASCII backslashes are removed, hence it is still valid UTF-8
+    core::str::from_utf8(&buffer[..len]).ok()
+}
+
+#[inline(always)]
+//Checks whether `ch` separates `X-Forwarded-For` nodes in lenient mode
+fn is_lenient_x_sep(ch: char) -> bool {
+    ch == FORWARDED_SEP || ch.is_ascii_whitespace()
+}
+
+#[inline]
+//Returns number of non-empty components of `X-Forwarded-For` in lenient mode
+fn count_lenient_x_components(value: &str) -> usize {
+    value.split(is_lenient_x_sep).filter(|component| !component.is_empty()).count()
+}
+
+#[inline]
+//token characters as defined by RFC 7230
+const fn is_tchar(ch: u8) -> bool {
+    match ch {
+        b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~' => true,
+        ch => ch.is_ascii_alphanumeric(),
+    }
+}
+
+//Writes value as token if possible, otherwise as quoted-string
+fn write_value<W: fmt::Write>(out: &mut W, value: &str) -> fmt::Result {
+    if !value.is_empty() && value.bytes().all(is_tchar) {
+        return out.write_str(value);
+    }
+
+    out.write_char('"')?;
+    for ch in value.chars() {
+        if ch == '"' || ch == '\\' {
+            out.write_char('\\')?;
+        }
+        out.write_char(ch)?;
+    }
+    out.write_char('"')
+}
+
+//Writes node as `Forwarded` header's value
+fn write_node<W: fmt::Write>(out: &mut W, node: &ForwardedNode<'_>) -> fmt::Result {
+    match node {
+        ForwardedNode::Ip(IpAddr::V4(ip)) => write!(out, "{ip}"),
+        ForwardedNode::Ip(IpAddr::V6(ip)) => write!(out, "\"[{ip}]\""),
+        ForwardedNode::Name(name) => write_value(out, name),
+        ForwardedNode::Unknown => out.write_str("unknown"),
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 ///Parsed node of the Forwarded header
 ///
@@ -24,6 +286,26 @@ pub enum ForwardedNode<'a> {
 }
 
 impl<'a> ForwardedNode<'a> {
+    #[inline(always)]
+    ///Creates node with IP address
+    pub const fn from_ip(ip: IpAddr) -> Self {
+        Self::Ip(ip)
+    }
+
+    #[inline(always)]
+    ///Creates node with obfuscated identifier
+    ///
+    ///`name` is used as it is, without attempting to parse it as IP address
+    pub const fn from_name(name: &'a str) -> Self {
+        Self::Name(name)
+    }
+
+    #[inline(always)]
+    ///Creates node indicating that proxy cannot know IP
+    pub const fn unknown() -> Self {
+        Self::Unknown
+    }
+
     #[inline(always)]
     fn parse_name(name: &'a str) -> Self {
         if let Ok(name) = name.parse() {
@@ -42,41 +324,152 @@ impl<'a> ForwardedNode<'a> {
         }
     }
 
-    #[inline]
+    ///Attempts to recover IP address from node, which is not valid IP
+    ///
+    ///This is heuristic, which looks for IP literal at the start or at the end of the `Name`
+    ///(e.g. `ip=203.0.113.5` or `203.0.113.5/proxy`), as emitted by some misbehaving proxies.
+    ///Result should not be trusted more than the proxy which produced such node.
+    ///
+    ///For `Ip` returns its value, while for `Unknown` returns `None`
+    pub fn recover_ip(&self) -> Option<IpAddr> {
+        #[inline(always)]
+        fn is_ip_char(ch: char) -> bool {
+            ch.is_ascii_hexdigit() || ch == '.' || ch == ':'
+        }
+
+        match self {
+            Self::Ip(ip) => Some(*ip),
+            Self::Unknown => None,
+            Self::Name(name) => {
+                let name = name.trim_matches(|ch| ch == '"' || ch == '[' || ch == ']');
+                let leading = match name.find(|ch| !is_ip_char(ch)) {
+                    Some(end) => &name[..end],
+                    None => name,
+                };
+                let trailing = match name.rfind(|ch| !is_ip_char(ch)) {
+                    Some(start) => &name[start + 1..],
+                    None => name,
+                };
+
+                [leading, trailing].into_iter().find_map(|candidate| candidate.trim_matches(':').parse().ok())
+            }
+        }
+    }
+
     ///Parses X-Forwarded-For's `Node` identifier
+    ///
+    ///Besides plain IP address, it accepts address with port in form of `<ipv4>:<port>` and `[<ipv6>]:<port>`,
+    ///which is emitted by some proxies.
     pub fn parse_x_node(mut node: &'a str) -> Self {
         node = node.trim();
-        match node.parse() {
-            Ok(ip) => ForwardedNode::Ip(ip),
-            Err(_) => ForwardedNode::Name(node)
+        if let Ok(ip) = node.parse() {
+            return ForwardedNode::Ip(ip);
         }
+
+        if let Some(ipv6) = node.strip_prefix('[') {
+            if let Some((ip, port)) = ipv6.split_once(']') {
+                if let (Ok(ip), true) = (ip.parse::<Ipv6Addr>(), port.is_empty() || is_port(port)) {
+                    return ForwardedNode::Ip(IpAddr::V6(ip));
+                }
+            }
+        } else if let Some((ip, port)) = node.split_once(':') {
+            if let (Ok(ip), Ok(_)) = (ip.parse::<Ipv4Addr>(), port.parse::<u16>()) {
+                return ForwardedNode::Ip(IpAddr::V4(ip));
+            }
+        }
+
+        ForwardedNode::Name(node)
     }
 
+    #[inline(always)]
     ///Parses `Node` identifier
-    pub fn parse_node(mut node: &'a str) -> Self {
-        node = node.trim_matches('"');
+    ///
+    ///Bracketed IPv6 address followed by anything other than port is considered malformed and returned as `Name`
+    pub fn parse_node(node: &'a str) -> Self {
+        Self::parse_node_with_port(node).0
+    }
+
+    ///Parses `Node` identifier, returning port if it is specified
+    ///
+    ///Port is returned as it is, which can be either number or obfuscated identifier (e.g. `_abc`).
+    ///Node identifier itself is classified the same way as [parse_node](Self::parse_node),
+    ///hence both `_hidden` and `_hidden:8080` produce `Name("_hidden")`, but port is only present in the latter.
+    ///
+    ///Quoted value is unescaped before parsing IP address, while `Name` is returned as it is written within quotes.
+    pub fn parse_node_with_port(mut node: &'a str) -> (Self, Option<&'a str>) {
+        if node.len() >= 2 && node.starts_with('"') && node.ends_with('"') {
+            node = &node[1..node.len() - 1];
+            if node.contains('\\') {
+                return Self::parse_escaped_node(node);
+            }
+        } else {
+            node = node.trim_matches('"');
+        }
+
         if node.eq_ignore_ascii_case("unknown") {
-            return Self::Unknown;
+            return (Self::Unknown, None);
         }
 
-        if let Some(mut ipv6) = node.strip_prefix('[') {
-            if let Some(end_addr_idx) = ipv6.find(']') {
-                ipv6 = &ipv6[..end_addr_idx];
-                return Self::parse_name(ipv6);
+        if let Some(ipv6) = node.strip_prefix('[') {
+            if let Some((ipv6, port)) = ipv6.split_once(']') {
+                //Anything but port after closing bracket indicates malformed node
+                if port.is_empty() {
+                    return (Self::parse_name(ipv6), None);
+                } else if is_node_port(port) {
+                    return (Self::parse_name(ipv6), Some(&port[1..]));
+                } else {
+                    return (Self::Name(node), None);
+                }
             } else {
-                return Self::Name(ipv6);
+                return (Self::Name(ipv6), None);
             }
         }
 
-        let mut node = node.rsplit(':');
-        let port_or_ip = node.next().unwrap();
-        let ip = if let Some(ip) = node.next() {
-            ip
-        } else {
-            port_or_ip
-        };
+        //Some proxies omit brackets around IPv6, which is not valid syntax, but it is unambiguous without port
+        if let Ok(ip) = node.parse() {
+            return (Self::Ip(ip), None);
+        }
+
+        match node.rsplit_once(':') {
+            //Same as with brackets, invalid port indicates malformed node
+            Some((ip, port)) => if is_node_port(&node[ip.len()..]) {
+                (ForwardedNode::parse_name(ip), Some(port))
+            } else {
+                (Self::Name(node), None)
+            },
+            None => (ForwardedNode::parse_name(node), None),
+        }
+    }
+
+    //Parses content of quoted string, containing escaped characters
+    fn parse_escaped_node(node: &'a str) -> (Self, Option<&'a str>) {
+        let mut buffer = [0u8; UNESCAPE_BUFFER_SIZE];
+        if let Some(unescaped) = unescape(node, &mut buffer) {
+            if let (ForwardedNode::Ip(ip), port) = ForwardedNode::parse_node_with_port(unescaped) {
+                //Port can only be borrowed from original value, when it is written without escapes
+                let port = port.and_then(|port| node.rsplit_once(':').map(|(_, original)| original).filter(|original| *original == port));
+                return (Self::Ip(ip), port);
+            }
+        }
+
+        (Self::Name(node), None)
+    }
+}
+
+impl From<IpAddr> for ForwardedNode<'_> {
+    #[inline(always)]
+    fn from(ip: IpAddr) -> Self {
+        Self::Ip(ip)
+    }
+}
 
-        ForwardedNode::parse_name(ip)
+impl PartialEq<IpAddr> for ForwardedNode<'_> {
+    #[inline(always)]
+    fn eq(&self, other: &IpAddr) -> bool {
+        match self {
+            Self::Ip(ip) => ip == other,
+            _ => false,
+        }
     }
 }
 
@@ -107,12 +500,21 @@ pub enum ForwardedValue<'a> {
     ///String with protocol name
     ///
     ///<https://datatracker.ietf.org/doc/html/rfc7239#section-5.4>
-    Protocol(&'a str)
+    Protocol(&'a str),
+    ///Extension parameter as key and raw value
+    ///
+    ///Only produced by [ForwardedEntryIter::parse_entry_with_extensions](struct.ForwardedEntryIter.html#method.parse_entry_with_extensions)
+    ///
+    ///<https://datatracker.ietf.org/doc/html/rfc7239#section-5.5>
+    Extension(&'a str, &'a str),
 }
 
+#[derive(Clone)]
 ///Iterator of `Forwarded` entry's components
 pub struct ForwardedEntryIter<'a> {
-    components: core::str::Split<'a, char>,
+    components: QuotedSplit<'a, 1>,
+    is_lenient: bool,
+    with_extensions: bool,
 }
 
 impl<'a> ForwardedEntryIter<'a> {
@@ -120,14 +522,59 @@ impl<'a> ForwardedEntryIter<'a> {
     ///
     ///It performs no error checking, ignoring invalid values, as it assumes you parse valid `Forwarded` header
     ///
+    ///Empty `for` and `by` values are skipped
+    ///
     ///Values within entry is separated by `;`
     ///
     ///This iterator returns [ForwardedValue](enum.ForwardedValue.html)
     pub fn parse_entry(value: &'a str) -> Self {
         Self {
-            components: value.split(ENTRY_SEP)
+            components: QuotedSplit::new(value, [ENTRY_SEP as u8]),
+            is_lenient: false,
+            with_extensions: false,
         }
     }
+
+    ///Variant of [parse_entry](#method.parse_entry), which additionally returns unknown parameters as [ForwardedValue::Extension](enum.ForwardedValue.html#variant.Extension)
+    ///
+    ///This allows to inspect extensions (e.g. `secret=`), which are otherwise ignored
+    pub fn parse_entry_with_extensions(value: &'a str) -> Self {
+        Self {
+            components: QuotedSplit::new(value, [ENTRY_SEP as u8]),
+            is_lenient: false,
+            with_extensions: true,
+        }
+    }
+
+    ///Lenient variant of [parse_entry](#method.parse_entry)
+    ///
+    ///Additionally treats bare IP address without key as `For` node, which is emitted by some misconfigured proxies
+    pub fn parse_entry_lenient(value: &'a str) -> Self {
+        Self {
+            components: QuotedSplit::new(value, [ENTRY_SEP as u8]),
+            is_lenient: true,
+            with_extensions: false,
+        }
+    }
+}
+
+#[inline]
+//Parses node, unless its value is empty, in which case it is skipped
+fn parse_non_empty_node(node: &str) -> Option<ForwardedNode<'_>> {
+    if node.trim_matches('"').is_empty() {
+        None
+    } else {
+        Some(ForwardedNode::parse_node(node))
+    }
+}
+
+#[inline]
+//Parses component without key as IP node, returning `None` if it is not IP
+fn parse_implicit_for_node(component: &str) -> Option<ForwardedNode<'_>> {
+    match ForwardedNode::parse_x_node(component) {
+        ForwardedNode::Ip(ip) => Some(ForwardedNode::Ip(ip)),
+        _ => None,
+    }
 }
 
 impl<'a> Iterator for ForwardedEntryIter<'a> {
@@ -135,15 +582,21 @@ impl<'a> Iterator for ForwardedEntryIter<'a> {
 
     fn next(&mut self) -> Option<Self::Item> {
         while let Some(value) = self.components.next() {
+            //Entries may be separated with optional whitespace
+            let value = value.trim();
             let mut pairs = value.splitn(2, PAIR_SEP);
             let key = pairs.next().unwrap();
             if key.eq_ignore_ascii_case("for") {
-                if let Some(node) = pairs.next() {
-                    return Some(ForwardedValue::For(ForwardedNode::parse_node(node)))
+                if let Some(node) = pairs.next().and_then(parse_non_empty_node) {
+                    return Some(ForwardedValue::For(node))
+                }
+            } else if self.is_lenient && !value.contains(PAIR_SEP) {
+                if let Some(node) = parse_implicit_for_node(value) {
+                    return Some(ForwardedValue::For(node))
                 }
             } else if key.eq_ignore_ascii_case("by") {
-                if let Some(node) = pairs.next() {
-                    return Some(ForwardedValue::By(ForwardedNode::parse_node(node)))
+                if let Some(node) = pairs.next().and_then(parse_non_empty_node) {
+                    return Some(ForwardedValue::By(node))
                 }
             } else if key.eq_ignore_ascii_case("proto") {
                 if let Some(proto) = pairs.next() {
@@ -153,6 +606,10 @@ impl<'a> Iterator for ForwardedEntryIter<'a> {
                 if let Some(host) = pairs.next() {
                     return Some(ForwardedValue::Host(host))
                 }
+            } else if self.with_extensions {
+                if let Some(value) = pairs.next() {
+                    return Some(ForwardedValue::Extension(key, value))
+                }
             }
         }
 
@@ -160,6 +617,67 @@ impl<'a> Iterator for ForwardedEntryIter<'a> {
     }
 }
 
+impl FusedIterator for ForwardedEntryIter<'_> {}
+
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+///Single entry of `Forwarded` header with its parameters grouped together
+///
+///If parameter is specified multiple times, only first value is used
+pub struct ForwardedEntry<'a> {
+    ///`for` parameter
+    pub for_: Option<ForwardedNode<'a>>,
+    ///`by` parameter
+    pub by: Option<ForwardedNode<'a>>,
+    ///`host` parameter
+    pub host: Option<&'a str>,
+    ///`proto` parameter
+    pub proto: Option<&'a str>,
+}
+
+impl<'a> ForwardedEntry<'a> {
+    ///Parses single entry within `Forwarded` header
+    ///
+    ///See [ForwardedEntryIter::parse_entry](struct.ForwardedEntryIter.html#method.parse_entry) for details
+    pub fn parse(value: &'a str) -> Self {
+        let mut entry = Self::default();
+        for value in ForwardedEntryIter::parse_entry(value) {
+            match value {
+                ForwardedValue::By(node) => {
+                    entry.by.get_or_insert(node);
+                },
+                ForwardedValue::For(node) => {
+                    entry.for_.get_or_insert(node);
+                },
+                ForwardedValue::Host(host) => {
+                    entry.host.get_or_insert(host);
+                },
+                ForwardedValue::Protocol(proto) => {
+                    entry.proto.get_or_insert(proto);
+                },
+                ForwardedValue::Extension(..) => (),
+            }
+        }
+        entry
+    }
+}
+
+///Iterator over entries within `Forwarded` header, grouping each entry's parameters as [ForwardedEntry](struct.ForwardedEntry.html)
+pub struct HopIter<'a, I> {
+    components: I,
+    _lifetime: marker::PhantomData<&'a I>,
+}
+
+impl<'a, I: Iterator<Item = &'a str> + 'a> Iterator for HopIter<'a, I> {
+    type Item = ForwardedEntry<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.components.next().map(ForwardedEntry::parse)
+    }
+}
+
+impl<'a, I: FusedIterator<Item = &'a str> + 'a> FusedIterator for HopIter<'a, I> {}
+
 ///Iterator over entries components within `Forwarded` header
 pub struct ForwardedIter<'a, I> {
     components: I,
@@ -175,6 +693,8 @@ impl<'a, I: Iterator<Item = &'a str> + 'a> Iterator for ForwardedIter<'a, I> {
     }
 }
 
+impl<'a, I: FusedIterator<Item = &'a str> + 'a> FusedIterator for ForwardedIter<'a, I> {}
+
 ///Iterator over `For` components within `Forwarded` header
 ///
 ///This is most likely what you need most of the time in order to determine client's actual IP, but
@@ -182,6 +702,9 @@ impl<'a, I: Iterator<Item = &'a str> + 'a> Iterator for ForwardedIter<'a, I> {
 ///components
 pub struct ForwardedForIter<'a, I> {
     components: I,
+    //Upper bound of remaining components
+    remaining: usize,
+    is_lenient: bool,
     _lifetime: marker::PhantomData<&'a I>,
 }
 
@@ -191,24 +714,43 @@ impl<'a, I: Iterator<Item = &'a str> + 'a> Iterator for ForwardedForIter<'a, I>
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
         while let Some(value) = self.components.next() {
+            self.remaining = self.remaining.saturating_sub(1);
+            //Entries may be separated with optional whitespace
+            let value = value.trim();
             let mut pairs = value.splitn(2, PAIR_SEP);
             let key = pairs.next().unwrap();
-            if key.eq_ignore_ascii_case("for") {
-                if let Some(node) = pairs.next() {
-                    return Some(ForwardedNode::parse_node(node))
+            match pairs.next() {
+                Some(node) => if key.eq_ignore_ascii_case("for") {
+                    if let Some(node) = parse_non_empty_node(node) {
+                        return Some(node)
+                    }
+                },
+                None => if self.is_lenient {
+                    if let Some(node) = parse_implicit_for_node(key) {
+                        return Some(node)
+                    }
                 }
             }
         }
 
         None
     }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining))
+    }
 }
 
+impl<'a, I: FusedIterator<Item = &'a str> + 'a> FusedIterator for ForwardedForIter<'a, I> {}
+
 ///Iterator over `X-Forwarded-For` header
 ///
 ///This header is not standard and iterator assumes it is simple list of IP addresses.
 pub struct XForwardedForIter<'a, I> {
     components: I,
+    //Number of remaining components
+    remaining: usize,
     _lifetime: marker::PhantomData<&'a I>,
 }
 
@@ -217,10 +759,21 @@ impl<'a, I: Iterator<Item = &'a str> + 'a> Iterator for XForwardedForIter<'a, I>
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        self.components.next().map(ForwardedNode::parse_x_node)
+        let component = self.components.next()?;
+        self.remaining = self.remaining.saturating_sub(1);
+        Some(ForwardedNode::parse_x_node(component))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
     }
 }
 
+impl<'a, I: FusedIterator<Item = &'a str> + 'a> FusedIterator for XForwardedForIter<'a, I> {}
+
+impl<'a, I: Iterator<Item = &'a str> + 'a> ExactSizeIterator for XForwardedForIter<'a, I> {}
+
 #[inline(always)]
 ///Parses provided string as `Forwarded` header
 ///
@@ -229,54 +782,379 @@ impl<'a, I: Iterator<Item = &'a str> + 'a> Iterator for XForwardedForIter<'a, I>
 ///Every proxy's entry is separated by `,`
 ///
 ///This iterator returns iterator over individual proxy's entries within `value`
-pub fn parse_forwarded<'a>(value: &'a str) -> ForwardedIter<'a, impl Iterator<Item = &'a str>> {
+pub fn parse_forwarded<'a>(value: &'a str) -> ForwardedIter<'a, impl FusedIterator<Item = &'a str>> {
     ForwardedIter {
-        components: value.split(FORWARDED_SEP),
+        components: QuotedSplit::new(value, [FORWARDED_SEP as u8]),
         _lifetime: marker::PhantomData,
     }
 }
 
 #[inline(always)]
 ///Variant of [parse_forwarded](fn.parse_forwarded.html) that reverses order of output
-pub fn parse_forwarded_rev<'a>(value: &'a str) -> ForwardedIter<'a, impl Iterator<Item = &'a str>> {
+pub fn parse_forwarded_rev<'a>(value: &'a str) -> ForwardedIter<'a, impl FusedIterator<Item = &'a str>> {
     ForwardedIter {
-        components: value.rsplit(FORWARDED_SEP),
+        components: QuotedSplit::new(value, [FORWARDED_SEP as u8]).rev(),
+        _lifetime: marker::PhantomData,
+    }
+}
+
+#[inline(always)]
+///Parses provided string as `Forwarded` header returning every proxy's entry in order
+pub fn parse_hops<'a>(value: &'a str) -> HopIter<'a, impl FusedIterator<Item = &'a str>> {
+    HopIter {
+        components: QuotedSplit::new(value, [FORWARDED_SEP as u8]),
+        _lifetime: marker::PhantomData,
+    }
+}
+
+#[inline(always)]
+///Variant of [parse_hops](fn.parse_hops.html) that reverses order of output, starting from the nearest proxy
+pub fn parse_hops_rev<'a>(value: &'a str) -> HopIter<'a, impl FusedIterator<Item = &'a str>> {
+    HopIter {
+        components: QuotedSplit::new(value, [FORWARDED_SEP as u8]).rev(),
         _lifetime: marker::PhantomData,
     }
 }
 
 #[inline(always)]
 ///Parses provided string as `Forwarded` header returning all `For` nodes in order
-pub fn parse_forwarded_for<'a>(value: &'a str) -> ForwardedForIter<'a, impl Iterator<Item = &'a str>> {
+pub fn parse_forwarded_for<'a>(value: &'a str) -> ForwardedForIter<'a, impl FusedIterator<Item = &'a str>> {
     ForwardedForIter {
-        components: value.split([FORWARDED_SEP, ENTRY_SEP]),
+        components: QuotedSplit::new(value, [FORWARDED_SEP as u8, ENTRY_SEP as u8]),
+        remaining: count_components(value, [FORWARDED_SEP, ENTRY_SEP]),
+        is_lenient: false,
         _lifetime: marker::PhantomData,
     }
 }
 
 #[inline(always)]
 ///Parses provided string as `Forwarded` header returning all `For` nodes in reverse order
-pub fn parse_forwarded_for_rev<'a>(value: &'a str) -> ForwardedForIter<'a, impl Iterator<Item = &'a str>> {
+pub fn parse_forwarded_for_rev<'a>(value: &'a str) -> ForwardedForIter<'a, impl FusedIterator<Item = &'a str>> {
+    ForwardedForIter {
+        components: QuotedSplit::new(value, [FORWARDED_SEP as u8, ENTRY_SEP as u8]).rev(),
+        remaining: count_components(value, [FORWARDED_SEP, ENTRY_SEP]),
+        is_lenient: false,
+        _lifetime: marker::PhantomData,
+    }
+}
+
+#[inline(always)]
+///Lenient variant of [parse_forwarded_for](fn.parse_forwarded_for.html)
+///
+///Additionally treats bare IP address without key as `For` node, which is emitted by some misconfigured proxies (e.g. `Forwarded: 203.0.113.1`)
+pub fn parse_forwarded_for_lenient<'a>(value: &'a str) -> ForwardedForIter<'a, impl FusedIterator<Item = &'a str>> {
+    ForwardedForIter {
+        components: QuotedSplit::new(value, [FORWARDED_SEP as u8, ENTRY_SEP as u8]),
+        remaining: count_components(value, [FORWARDED_SEP, ENTRY_SEP]),
+        is_lenient: true,
+        _lifetime: marker::PhantomData,
+    }
+}
+
+#[inline(always)]
+///Lenient variant of [parse_forwarded_for_rev](fn.parse_forwarded_for_rev.html)
+///
+///Additionally treats bare IP address without key as `For` node, which is emitted by some misconfigured proxies (e.g. `Forwarded: 203.0.113.1`)
+pub fn parse_forwarded_for_lenient_rev<'a>(value: &'a str) -> ForwardedForIter<'a, impl FusedIterator<Item = &'a str>> {
     ForwardedForIter {
-        components: value.rsplit([FORWARDED_SEP, ENTRY_SEP]),
+        components: QuotedSplit::new(value, [FORWARDED_SEP as u8, ENTRY_SEP as u8]).rev(),
+        remaining: count_components(value, [FORWARDED_SEP, ENTRY_SEP]),
+        is_lenient: true,
         _lifetime: marker::PhantomData,
     }
 }
 
 #[inline(always)]
 ///Parses provided string as `X-Forwarded-For` header returning all nodes in order
-pub fn parse_x_forwarded_for<'a>(value: &'a str) -> XForwardedForIter<'a, impl Iterator<Item = &'a str>> {
+pub fn parse_x_forwarded_for<'a>(value: &'a str) -> XForwardedForIter<'a, impl FusedIterator<Item = &'a str>> {
     XForwardedForIter {
         components: value.split(FORWARDED_SEP),
+        remaining: count_components(value, [FORWARDED_SEP]),
         _lifetime: marker::PhantomData,
     }
 }
 
+#[inline]
+///Parses provided string as `X-Forwarded-For` header with single node
+///
+///This is fast path for the most common case, skipping iterator setup.
+///
+///Returns `None` if value contains multiple nodes, in which case [parse_x_forwarded_for] should be used
+pub fn parse_x_forwarded_for_single(value: &str) -> Option<ForwardedNode<'_>> {
+    if value.contains(FORWARDED_SEP) {
+        None
+    } else {
+        Some(ForwardedNode::parse_x_node(value))
+    }
+}
+
 #[inline(always)]
 ///Parses provided string as `X-Forwarded-For` header returning all nodes in reverse order
-pub fn parse_x_forwarded_for_rev<'a>(value: &'a str) -> XForwardedForIter<'a, impl Iterator<Item = &'a str>> {
+pub fn parse_x_forwarded_for_rev<'a>(value: &'a str) -> XForwardedForIter<'a, impl FusedIterator<Item = &'a str>> {
     XForwardedForIter {
         components: value.rsplit(FORWARDED_SEP),
+        remaining: count_components(value, [FORWARDED_SEP]),
         _lifetime: marker::PhantomData,
     }
 }
+
+#[inline(always)]
+///Parses provided string as `X-Forwarded-For` header returning all nodes in order
+///
+///Unlike [parse_x_forwarded_for], nodes can be separated by ASCII whitespace in addition to commas,
+///which is emitted by some misbehaving proxies (e.g. `203.0.113.1 198.51.100.2`)
+pub fn parse_x_forwarded_for_lenient<'a>(value: &'a str) -> XForwardedForIter<'a, impl FusedIterator<Item = &'a str>> {
+    XForwardedForIter {
+        components: value.split(is_lenient_x_sep).filter(|component| !component.is_empty()),
+        remaining: count_lenient_x_components(value),
+        _lifetime: marker::PhantomData,
+    }
+}
+
+#[inline(always)]
+///Parses provided string as `X-Forwarded-For` header returning all nodes in reverse order
+///
+///Unlike [parse_x_forwarded_for_rev], nodes can be separated by ASCII whitespace in addition to commas
+pub fn parse_x_forwarded_for_lenient_rev<'a>(value: &'a str) -> XForwardedForIter<'a, impl FusedIterator<Item = &'a str>> {
+    XForwardedForIter {
+        components: value.rsplit(is_lenient_x_sep).filter(|component| !component.is_empty()),
+        remaining: count_lenient_x_components(value),
+        _lifetime: marker::PhantomData,
+    }
+}
+
+struct DebugChain<'a> {
+    value: &'a str,
+    is_x_forwarded: bool,
+}
+
+impl fmt::Debug for DebugChain<'_> {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_x_forwarded {
+            fmt.debug_list().entries(parse_x_forwarded_for(self.value)).finish()
+        } else {
+            fmt.debug_list().entries(parse_forwarded_for(self.value)).finish()
+        }
+    }
+}
+
+#[inline(always)]
+///Returns `Debug` representation of every `for` node within `Forwarded` header `value`
+///
+///Output is stable and performs no allocation, e.g. `[Ip(203.0.113.1), Unknown, Name("_hidden")]`.
+///Alternate format (`{:#?}`) prints each node on separate line.
+pub fn debug_chain(value: &str) -> impl fmt::Debug + '_ {
+    DebugChain {
+        value,
+        is_x_forwarded: false,
+    }
+}
+
+#[inline(always)]
+///Returns `Debug` representation of every node within `X-Forwarded-For` header `value`
+///
+///See [debug_chain] for details
+pub fn debug_x_chain(value: &str) -> impl fmt::Debug + '_ {
+    DebugChain {
+        value,
+        is_x_forwarded: true,
+    }
+}
+
+///Resolves client IP out of `Forwarded` header `values`, taking rightmost after filtering out any IP matching `filter`
+///
+///This is the same logic as used by integrations with `http` and `tonic`, suitable for any transport.
+///Values are scanned from last to first, hence iterator must be double ended (e.g. `values.iter().copied()`).
+///
+///Returns `None` if IP is not provided or obfuscated
+pub fn resolve_from_values<'a>(values: impl DoubleEndedIterator<Item = &'a str>, filter: &(impl crate::filter::Filter + ?Sized)) -> Option<IpAddr> {
+    crate::find_next_ip_after_filter(values.rev().flat_map(parse_forwarded_for_rev), filter)
+}
+
+///Writes single `Forwarded` entry into `out`, skipping parameters set to `None`
+///
+///Values are quoted when necessary, and IPv6 addresses are written within brackets as required by
+///[RFC 7239](https://datatracker.ietf.org/doc/html/rfc7239#section-6)
+///
+///This function performs no allocation, writing directly into `out`
+pub fn write_forwarded_entry<W: fmt::Write>(out: &mut W, by: Option<ForwardedNode<'_>>, r#for: Option<ForwardedNode<'_>>, host: Option<&str>, proto: Option<&str>) -> fmt::Result {
+    let mut sep = "";
+
+    if let Some(by) = by {
+        out.write_str("by=")?;
+        write_node(out, &by)?;
+        sep = ";";
+    }
+
+    if let Some(r#for) = r#for {
+        out.write_str(sep)?;
+        out.write_str("for=")?;
+        write_node(out, &r#for)?;
+        sep = ";";
+    }
+
+    if let Some(host) = host {
+        out.write_str(sep)?;
+        out.write_str("host=")?;
+        write_value(out, host)?;
+        sep = ";";
+    }
+
+    if let Some(proto) = proto {
+        out.write_str(sep)?;
+        out.write_str("proto=")?;
+        write_value(out, proto)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug)]
+///Formatter of `for` parameter of the `Forwarded` header
+///
+///Created via [format_for_node] or [format_for_socket_addr]
+pub struct ForNodeFmt<'a> {
+    node: ForwardedNode<'a>,
+    port: Option<u16>,
+}
+
+impl fmt::Display for ForNodeFmt<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str("for=")?;
+        match (self.node, self.port) {
+            (ForwardedNode::Ip(IpAddr::V4(ip)), Some(port)) => write!(fmt, "\"{ip}:{port}\""),
+            (ForwardedNode::Ip(IpAddr::V6(ip)), Some(port)) => write!(fmt, "\"[{ip}]:{port}\""),
+            (node, _) => write_node(fmt, &node),
+        }
+    }
+}
+
+#[inline(always)]
+///Renders `for` parameter with `node`, quoting it as required by
+///[RFC 7239](https://datatracker.ietf.org/doc/html/rfc7239#section-6)
+///
+///E.g. `for=192.0.2.1` or `for="[2001:db8::1]"`
+pub const fn format_for_node(node: ForwardedNode<'_>) -> ForNodeFmt<'_> {
+    ForNodeFmt {
+        node,
+        port: None,
+    }
+}
+
+#[inline(always)]
+///Renders `for` parameter with `addr`, including port
+///
+///E.g. `for="192.0.2.1:4711"` or `for="[2001:db8::1]:4711"`
+pub const fn format_for_socket_addr(addr: SocketAddr) -> ForNodeFmt<'static> {
+    ForNodeFmt {
+        node: ForwardedNode::Ip(addr.ip()),
+        port: Some(addr.port()),
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, PartialEq, Eq, Debug)]
+///Owned version of [ForwardedNode]
+pub enum OwnedForwardedNode {
+    ///Proxy specified real IP address
+    Ip(IpAddr),
+    ///Proxy decided to obscure
+    Name(alloc::string::String),
+    ///Proxy indicates it cannot know IP
+    Unknown,
+}
+
+#[cfg(feature = "alloc")]
+impl OwnedForwardedNode {
+    #[inline(always)]
+    ///Returns `ip` value if node is valid IP address
+    pub const fn ip(&self) -> Option<IpAddr> {
+        match self {
+            Self::Ip(ip) => Some(*ip),
+            _ => None
+        }
+    }
+
+    #[inline]
+    ///Returns borrowed node
+    pub fn as_node(&self) -> ForwardedNode<'_> {
+        match self {
+            Self::Ip(ip) => ForwardedNode::Ip(*ip),
+            Self::Name(name) => ForwardedNode::Name(name.as_str()),
+            Self::Unknown => ForwardedNode::Unknown,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<ForwardedNode<'_>> for OwnedForwardedNode {
+    #[inline]
+    fn from(node: ForwardedNode<'_>) -> Self {
+        match node {
+            ForwardedNode::Ip(ip) => Self::Ip(ip),
+            ForwardedNode::Name(name) => Self::Name(name.into()),
+            ForwardedNode::Unknown => Self::Unknown,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+///Owned proxy chain, which does not borrow header value
+///
+///This allows to keep chain across await points or move it into spawned task
+pub struct OwnedForwardedChain {
+    nodes: alloc::vec::Vec<OwnedForwardedNode>,
+}
+
+#[cfg(feature = "alloc")]
+impl OwnedForwardedChain {
+    #[inline]
+    ///Parses every `for` node of `Forwarded` header `value`
+    pub fn parse_forwarded(value: &str) -> Self {
+        parse_forwarded_for(value).collect()
+    }
+
+    #[inline]
+    ///Parses every node of `X-Forwarded-For` header `value`
+    pub fn parse_x_forwarded_for(value: &str) -> Self {
+        parse_x_forwarded_for(value).collect()
+    }
+
+    #[inline(always)]
+    ///Returns nodes in order from left to right
+    pub fn nodes(&self) -> &[OwnedForwardedNode] {
+        &self.nodes
+    }
+
+    #[inline(always)]
+    ///Returns iterator over borrowed nodes in order from left to right
+    ///
+    ///Use `rev()` to iterate from right to left, e.g. to pass it to [find_next_ip_after_filter](crate::find_next_ip_after_filter)
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = ForwardedNode<'_>> + ExactSizeIterator + FusedIterator {
+        self.nodes.iter().map(OwnedForwardedNode::as_node)
+    }
+
+    #[inline(always)]
+    ///Returns number of nodes
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[inline(always)]
+    ///Returns whether chain has no nodes
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a> FromIterator<ForwardedNode<'a>> for OwnedForwardedChain {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = ForwardedNode<'a>>>(nodes: I) -> Self {
+        Self {
+            nodes: nodes.into_iter().map(OwnedForwardedNode::from).collect(),
+        }
+    }
+}
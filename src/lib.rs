@@ -2,10 +2,19 @@
 //!
 //! ## Features
 //!
+//! - `alloc` - Enables functionality that requires allocation;
+//! - `std` - Enables functionality that requires standard library (implies `alloc`);
+//! - `smallvec` - Enables methods collecting IPs into `SmallVec`;
 //! - `http` - Enables filter implementation using http's header map;
 //! - `axum08` - Enables `axum` extractor implementation for `0.8.x`;
 //! - `tonic014` - Enables `tonic` extension implementation for `0.14.x`.
-//! - `ohkami024` - Enables `ohkami` extension implementation for `0.24.x`.
+//! - `ohkami024` - Enables `ohkami` extension implementation for `0.24.x`;
+//! - `test-util` - Enables `test_util` module with helpers to build synthetic headers for testing own filters;
+//! - `serde` - Enables `serde` support for `Cidr` and, together with `alloc`, deserializable `FilterConfig`;
+//! - `ipnet` - Enables `Filter` implementation and conversions for `ipnet` types;
+//! - `cidr` - Enables `Filter` implementation and conversions for `cidr` types;
+//! - `cloud-ranges` - Enables parsers of cloud providers' published IP ranges in JSON format;
+//! - `maxminddb` - Enables country lookup in GeoIP2/GeoLite2 database via `maxminddb::Reader`. Implies `std`.
 //!
 //! ## Example
 //!
@@ -37,12 +46,18 @@
 #![warn(missing_docs)]
 #![allow(clippy::style)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::net::IpAddr;
 
 #[cfg(any(feature = "tonic014", feature = "http"))]
 mod shared;
 pub mod forwarded;
 pub mod filter;
+pub mod extract;
 #[cfg(feature = "http")]
 pub mod http;
 #[cfg(feature = "axum08")]
@@ -51,12 +66,16 @@ pub mod axum08;
 pub mod tonic014;
 #[cfg(feature = "ohkami024")]
 pub mod ohkami024;
+#[cfg(feature = "test-util")]
+pub mod test_util;
 
 #[inline]
 ///Determines next IP among `nodes` iterator after applying filter
 ///
 ///If `node` is not IP address, then search is aborted, as it is impossible to correctly apply filter
-pub fn find_next_ip_after_filter<'a>(nodes: impl Iterator<Item = forwarded::ForwardedNode<'a>>, filter: &impl filter::Filter) -> Option<IpAddr> {
+///
+///`filter` can be trait object (e.g. `&dyn Filter`), allowing to select filter at runtime
+pub fn find_next_ip_after_filter<'a>(nodes: impl Iterator<Item = forwarded::ForwardedNode<'a>>, filter: &(impl filter::Filter + ?Sized)) -> Option<IpAddr> {
 
     for node in nodes {
         match node {
@@ -12,7 +12,7 @@ use ohkami024::{FromRequest, Request, Response};
 #[derive(Copy, Clone)]
 ///ClientIp extractor
 ///
-///Provided `F` parameter can be used to customize filter selection. Use `nil` type to only extract rightmost IP.
+///Provided `F` parameter can be used to customize filter selection. Use [MatchNone](../filter/struct.MatchNone.html) to only extract rightmost IP.
 pub struct ClientIp<F: Filter> {
     ///Underlying IP addr if available
     pub inner: Option<IpAddr>,
@@ -1,5 +1,203 @@
+use core::iter;
+use core::net::IpAddr;
+
+use crate::filter::Filter;
+use crate::extract::HopKind;
+use crate::forwarded::ForwardedNode;
+
 pub const FALLBACK_STR: &str = "<non-utf8>";
 
+//Prevents implementation of extension traits outside of this crate, so that new methods can be added without breaking changes
+pub trait Sealed {}
+
+#[inline]
+//Applies filter on `nodes`, returning `None` if there are no nodes
+pub fn find_filtered_ip<'a>(nodes: impl Iterator<Item = ForwardedNode<'a>>, filter: &(impl Filter + ?Sized)) -> Option<Option<IpAddr>> {
+    let mut nodes = nodes.peekable();
+    nodes.peek()?;
+    Some(crate::find_next_ip_after_filter(nodes, filter))
+}
+
+//Nodes of the proxy chain, taken from `Forwarded` header if it has any node, otherwise from `X-Forwarded-For`
+pub enum ChainNodes<F: Iterator, X> {
+    Forwarded(iter::Peekable<F>),
+    XForwardedFor(X),
+}
+
+impl<'a, F: Iterator<Item = ForwardedNode<'a>>, X: Iterator<Item = ForwardedNode<'a>>> ChainNodes<F, X> {
+    #[inline]
+    pub fn new(forwarded: F, x_forwarded: X) -> Self {
+        let mut forwarded = forwarded.peekable();
+        if forwarded.peek().is_some() {
+            Self::Forwarded(forwarded)
+        } else {
+            Self::XForwardedFor(x_forwarded)
+        }
+    }
+}
+
+impl<'a, F: Iterator<Item = ForwardedNode<'a>>, X: Iterator<Item = ForwardedNode<'a>>> Iterator for ChainNodes<F, X> {
+    type Item = ForwardedNode<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Forwarded(nodes) => nodes.next(),
+            Self::XForwardedFor(nodes) => nodes.next(),
+        }
+    }
+}
+
+#[inline]
+//Same as `find_next_ip_after_filter`, but returns `None` if there are more than `max_nodes` nodes
+//
+//Nodes after result are only counted, stopping as soon as limit is exceeded
+pub fn find_next_ip_after_filter_bounded<'a>(nodes: impl Iterator<Item = ForwardedNode<'a>>, filter: &(impl Filter + ?Sized), max_nodes: usize) -> Option<IpAddr> {
+    let mut result = None;
+    let mut is_resolved = false;
+    for (count, node) in nodes.enumerate() {
+        if count >= max_nodes {
+            return None;
+        } else if is_resolved {
+            continue;
+        }
+
+        match node {
+            ForwardedNode::Ip(ip) => if !filter.is_match(ip) {
+                result = Some(ip);
+                is_resolved = true;
+            },
+            _ => is_resolved = true,
+        }
+    }
+
+    result
+}
+
+#[inline]
+//Returns first IP matching `filter`, aborting on node without IP
+pub fn find_first_ip_matching_filter<'a>(nodes: impl Iterator<Item = ForwardedNode<'a>>, filter: &(impl Filter + ?Sized)) -> Option<IpAddr> {
+    for node in nodes {
+        match node {
+            ForwardedNode::Ip(ip) => if filter.is_match(ip) {
+                return Some(ip);
+            },
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+#[inline]
+//Returns first IP not matching `filter` together with number of IPs matching `filter` before it, aborting on node without IP
+pub fn find_ip_with_depth<'a>(nodes: impl Iterator<Item = ForwardedNode<'a>>, filter: &(impl Filter + ?Sized)) -> Option<(IpAddr, usize)> {
+    let mut depth = 0usize;
+    for node in nodes {
+        match node {
+            ForwardedNode::Ip(ip) => if filter.is_match(ip) {
+                depth = depth.saturating_add(1);
+            } else {
+                return Some((ip, depth));
+            },
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+#[inline]
+//Selects IP out of single hop's nodes, which do not match `filter`, preferring IPv4
+//
+//Returns `None` if every node matches `filter`, `Some(None)` if there is node without IP and no IP candidate
+pub fn select_hop_ip_prefer_v4<'a>(nodes: impl Iterator<Item = ForwardedNode<'a>>, filter: &(impl Filter + ?Sized)) -> Option<Option<IpAddr>> {
+    let mut ipv6 = None;
+    let mut is_obfuscated = false;
+    for node in nodes {
+        match node {
+            ForwardedNode::Ip(ip) => if filter.is_match(ip) {
+                continue
+            } else if ip.is_ipv4() {
+                return Some(Some(ip));
+            } else {
+                ipv6.get_or_insert(ip);
+            },
+            _ => is_obfuscated = true,
+        }
+    }
+
+    match ipv6 {
+        Some(ip) => Some(Some(ip)),
+        None if is_obfuscated => Some(None),
+        None => None,
+    }
+}
+
+#[inline]
+//Returns first IP classified as client, skipping trusted hops and aborting on rejected hop or node without IP
+pub fn resolve_by<'a>(nodes: impl Iterator<Item = ForwardedNode<'a>>, classify: impl Fn(IpAddr) -> HopKind) -> Option<IpAddr> {
+    for node in nodes {
+        match node {
+            ForwardedNode::Ip(ip) => match classify(ip) {
+                HopKind::Trusted => continue,
+                HopKind::Client => return Some(ip),
+                HopKind::Reject => return None,
+            },
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+#[inline]
+//Returns first IP not matching `trusted` filter, only if at least `min_trusted_hops` IPs matched before it
+pub fn find_ip_after_trusted_hops<'a>(nodes: impl Iterator<Item = ForwardedNode<'a>>, trusted: &(impl Filter + ?Sized), min_trusted_hops: usize) -> Option<IpAddr> {
+    let mut trusted_hops = 0usize;
+    for node in nodes {
+        match node {
+            ForwardedNode::Ip(ip) => if trusted.is_match(ip) {
+                trusted_hops = trusted_hops.saturating_add(1);
+            } else if trusted_hops >= min_trusted_hops {
+                return Some(ip);
+            } else {
+                return None;
+            },
+            _ => return None,
+        }
+    }
+
+    None
+}
+
+//Creates `ChainNodes` in reverse order
+macro_rules! chain_nodes {
+    ($this:expr) => {{
+        let forwarded = $this.get_all(FORWARDED)
+                             .into_iter()
+                             .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_forwarded_for(header));
+        let x_forwarded = $this.get_all(X_FORWARDED_FOR)
+                               .into_iter()
+                               .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_x_forwarded_for(header));
+        $crate::shared::ChainNodes::new(forwarded, x_forwarded)
+    }}
+}
+
+macro_rules! chain_nodes_rev {
+    ($this:expr) => {{
+        let forwarded = $this.get_all(FORWARDED)
+                             .into_iter()
+                             .rev()
+                             .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_forwarded_for_rev(header));
+        let x_forwarded = $this.get_all(X_FORWARDED_FOR)
+                               .into_iter()
+                               .rev()
+                               .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_x_forwarded_for_rev(header));
+        $crate::shared::ChainNodes::new(forwarded, x_forwarded)
+    }}
+}
+
 //Implementation of header map extensions cannot be easily shared due to tonic's Metadata not providing reference access to HeaderMap
 //Make it plain functions later if it becomes available one day
 macro_rules! impl_extract_leftmost_forwarded_ip {
@@ -7,7 +205,10 @@ macro_rules! impl_extract_leftmost_forwarded_ip {
         if let Some(forwarded) = $this.get_all(FORWARDED).into_iter().next() {
             forwarded.to_str().ok().and_then(|header| parse_forwarded_for(header).next()).and_then(|node| node.ip())
         } else if let Some(x_forwarded) = $this.get_all(X_FORWARDED_FOR).into_iter().next() {
-            x_forwarded.to_str().ok().and_then(|header| parse_x_forwarded_for(header).next()).and_then(|node| node.ip())
+            x_forwarded.to_str().ok().and_then(|header| match forwarded::parse_x_forwarded_for_single(header) {
+                Some(node) => Some(node),
+                None => parse_x_forwarded_for(header).next(),
+            }).and_then(|node| node.ip())
         } else {
             None
         }
@@ -19,7 +220,10 @@ macro_rules! impl_extract_rightmost_forwarded_ip {
         if let Some(forwarded) = $this.get_all(FORWARDED).into_iter().next_back() {
             forwarded.to_str().ok().and_then(|header| parse_forwarded_for_rev(header).next()).and_then(|node| node.ip())
         } else if let Some(x_forwarded) = $this.get_all(X_FORWARDED_FOR).into_iter().next_back() {
-            x_forwarded.to_str().ok().and_then(|header| parse_x_forwarded_for_rev(header).next()).and_then(|node| node.ip())
+            x_forwarded.to_str().ok().and_then(|header| match forwarded::parse_x_forwarded_for_single(header) {
+                Some(node) => Some(node),
+                None => parse_x_forwarded_for_rev(header).next(),
+            }).and_then(|node| node.ip())
         } else {
             None
         }
@@ -47,6 +251,17 @@ macro_rules! impl_extract_filtered_forwarded_ip {
         }
 
         if !forwarded_found {
+            let mut x_forwarded = $this.get_all(X_FORWARDED_FOR).into_iter();
+            //Fast path for single IP
+            if let (Some(header), None, 0) = (x_forwarded.next(), x_forwarded.next(), $skip) {
+                if let Some(node) = header.to_str().ok().and_then(forwarded::parse_x_forwarded_for_single) {
+                    return match node {
+                        forwarded::ForwardedNode::Ip(ip) if !$filter.is_match(ip) => Some(ip),
+                        _ => None,
+                    };
+                }
+            }
+
             let forwarded = $this.get_all(X_FORWARDED_FOR)
                                  .into_iter()
                                  .rev()
@@ -59,6 +274,347 @@ macro_rules! impl_extract_filtered_forwarded_ip {
     }}
 }
 
+macro_rules! impl_extract_ip_from {
+    ($this:expr, $key:expr) => {
+        $this.get_all($key).into_iter().next_back().and_then(|header| header.to_str().ok()).and_then(|header| forwarded::ForwardedNode::parse_x_node(header).ip())
+    }
+}
+
+macro_rules! impl_extract_filtered_ip_from {
+    ($this:expr, $key:expr, $filter:expr) => {{
+        let nodes = $this.get_all($key)
+                         .into_iter()
+                         .rev()
+                         .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_x_forwarded_for_rev(header));
+        crate::find_next_ip_after_filter(nodes, $filter)
+    }}
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_explain_filtered_forwarded_ip {
+    ($this:expr, $filter:expr) => {{
+        let forwarded = $this.get_all(FORWARDED)
+                             .into_iter()
+                             .rev()
+                             .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_forwarded_for_rev(header));
+        let x_forwarded = $this.get_all(X_FORWARDED_FOR)
+                               .into_iter()
+                               .rev()
+                               .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_x_forwarded_for_rev(header));
+
+        $crate::extract::Resolution::explain(forwarded, x_forwarded, $filter)
+    }}
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_collect_all_ips_with_source {
+    ($this:expr) => {{
+        use $crate::extract::HeaderSource;
+
+        let forwarded = $this.get_all(FORWARDED)
+                             .into_iter()
+                             .filter_map(|header| header.to_str().ok())
+                             .flat_map(|header| parse_forwarded_for(header))
+                             .map(|node| (node, HeaderSource::Forwarded));
+        let x_forwarded = $this.get_all(X_FORWARDED_FOR)
+                               .into_iter()
+                               .filter_map(|header| header.to_str().ok())
+                               .flat_map(|header| parse_x_forwarded_for(header))
+                               .map(|node| (node, HeaderSource::XForwardedFor));
+        let x_real_ip = $this.get_all(X_REAL_IP)
+                             .into_iter()
+                             .filter_map(|header| header.to_str().ok())
+                             .map(forwarded::ForwardedNode::parse_x_node)
+                             .map(|node| (node, HeaderSource::XRealIp));
+
+        forwarded.chain(x_forwarded).chain(x_real_ip).filter_map(|(node, source)| node.ip().map(|ip| (ip, source))).collect()
+    }}
+}
+
+#[cfg(feature = "alloc")]
+macro_rules! impl_distinct_chain_ips {
+    ($this:expr) => {{
+        let forwarded = $this.get_all(FORWARDED)
+                             .into_iter()
+                             .filter_map(|header| header.to_str().ok())
+                             .flat_map(|header| parse_forwarded_for(header));
+        let x_forwarded = $this.get_all(X_FORWARDED_FOR)
+                               .into_iter()
+                               .filter_map(|header| header.to_str().ok())
+                               .flat_map(|header| parse_x_forwarded_for(header));
+
+        let mut ips: alloc::vec::Vec<IpAddr> = forwarded.chain(x_forwarded).filter_map(|node| node.ip()).collect();
+        ips.sort_unstable();
+        ips.dedup();
+        ips
+    }}
+}
+
+macro_rules! impl_extract_filtered_forwarded_ip_with {
+    ($this:expr, $filter:expr, $config:expr) => {{
+        use $crate::extract::HeaderSource;
+        use $crate::shared::find_filtered_ip;
+
+        for source in $config.header_priority {
+            let result = match source {
+                HeaderSource::Forwarded => {
+                    let nodes = $this.get_all(FORWARDED)
+                                     .into_iter()
+                                     .rev()
+                                     .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_forwarded_for_rev(header));
+                    find_filtered_ip(nodes, $filter)
+                },
+                HeaderSource::XForwardedFor => {
+                    let nodes = $this.get_all(X_FORWARDED_FOR)
+                                     .into_iter()
+                                     .rev()
+                                     .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_x_forwarded_for_rev(header));
+                    find_filtered_ip(nodes, $filter)
+                },
+                HeaderSource::XRealIp => {
+                    let nodes = $this.get_all(X_REAL_IP)
+                                     .into_iter()
+                                     .rev()
+                                     .filter_map(|header| header.to_str().ok()).map(forwarded::ForwardedNode::parse_x_node);
+                    find_filtered_ip(nodes, $filter)
+                },
+            };
+
+            if let Some(result) = result {
+                return result;
+            }
+        }
+
+        None
+    }}
+}
+
+macro_rules! impl_extract_nearest_proxy_ip {
+    ($this:expr) => {{
+        let mut forwarded = $this.get_all(FORWARDED)
+                                 .into_iter()
+                                 .rev()
+                                 .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_forwarded_for_rev(header));
+        match forwarded.next() {
+            Some(node) => node.ip(),
+            None => $this.get_all(X_FORWARDED_FOR)
+                         .into_iter()
+                         .rev()
+                         .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_x_forwarded_for_rev(header))
+                         .next()
+                         .and_then(|node| node.ip()),
+        }
+    }}
+}
+
+#[cfg(feature = "smallvec")]
+macro_rules! impl_extract_chain {
+    ($this:expr) => {{
+        let mut chain = $this.get_all(FORWARDED)
+                             .into_iter()
+                             .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_forwarded_for(header))
+                             .filter_map(|node| node.ip())
+                             .collect::<smallvec::SmallVec<_>>();
+        if chain.is_empty() {
+            chain.extend($this.get_all(X_FORWARDED_FOR)
+                              .into_iter()
+                              .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_x_forwarded_for(header))
+                              .filter_map(|node| node.ip()));
+        }
+        chain
+    }}
+}
+
+macro_rules! impl_extract_first_trusted_forwarded_ip {
+    ($this:expr, $trusted:expr) => {
+        $crate::shared::find_first_ip_matching_filter($crate::shared::chain_nodes_rev!($this), $trusted)
+    }
+}
+
+macro_rules! impl_extract_filtered_leftmost_forwarded_ip {
+    ($this:expr, $filter:expr) => {
+        crate::find_next_ip_after_filter($crate::shared::chain_nodes!($this), $filter)
+    }
+}
+
+macro_rules! impl_extract_client_ip_leftmost_trusted {
+    ($this:expr) => {
+        $crate::shared::chain_nodes!($this).find_map(|node| node.ip())
+    }
+}
+
+macro_rules! impl_extract_client_ip_with_depth {
+    ($this:expr, $filter:expr) => {
+        $crate::shared::find_ip_with_depth($crate::shared::chain_nodes_rev!($this), $filter)
+    }
+}
+
+macro_rules! impl_extract_first_public_forwarded_ip {
+    ($this:expr) => {
+        $crate::shared::chain_nodes!($this).filter_map(|node| node.ip()).find(|ip| $crate::filter::is_global(*ip))
+    }
+}
+
+macro_rules! impl_extract_last_public_forwarded_ip {
+    ($this:expr) => {
+        $crate::shared::chain_nodes_rev!($this).filter_map(|node| node.ip()).find(|ip| $crate::filter::is_global(*ip))
+    }
+}
+
+macro_rules! impl_extract_rightmost_by_ip {
+    ($this:expr) => {
+        $this.get_all(FORWARDED)
+             .into_iter()
+             .rev()
+             .filter_map(|header| header.to_str().ok())
+             .flat_map(|header| forwarded::parse_forwarded_rev(header))
+             .find_map(|entry| entry.filter_map(|value| match value {
+                 forwarded::ForwardedValue::By(node) => Some(node),
+                 _ => None,
+             }).next())
+             .and_then(|node| node.ip())
+    }
+}
+
+macro_rules! impl_extract_filtered_forwarded_ip_bounded {
+    ($this:expr, $filter:expr, $max_nodes:expr) => {{
+        $crate::shared::find_next_ip_after_filter_bounded($crate::shared::chain_nodes_rev!($this), $filter, $max_nodes)
+    }}
+}
+
+macro_rules! impl_extract_filtered_forwarded_ip_prefer_v4 {
+    ($this:expr, $filter:expr) => {{
+        let entries = $this.get_all(FORWARDED)
+                           .into_iter()
+                           .rev()
+                           .filter_map(|header| header.to_str().ok()).flat_map(|header| forwarded::parse_forwarded_rev(header));
+
+        let mut forwarded_found = false;
+        for entry in entries {
+            let mut nodes = entry.filter_map(|value| match value {
+                forwarded::ForwardedValue::For(node) => Some(node),
+                _ => None,
+            }).peekable();
+            if nodes.peek().is_none() {
+                continue;
+            }
+
+            forwarded_found = true;
+            if let Some(ip) = $crate::shared::select_hop_ip_prefer_v4(nodes, $filter) {
+                return ip;
+            }
+        }
+
+        if forwarded_found {
+            None
+        } else {
+            let x_forwarded = $this.get_all(X_FORWARDED_FOR)
+                                   .into_iter()
+                                   .rev()
+                                   .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_x_forwarded_for_rev(header));
+            $crate::find_next_ip_after_filter(x_forwarded, $filter)
+        }
+    }}
+}
+
+macro_rules! impl_resolve_client_ip_by {
+    ($this:expr, $classify:expr) => {
+        $crate::shared::resolve_by($crate::shared::chain_nodes_rev!($this), $classify)
+    }
+}
+
+macro_rules! impl_extract_client_ip_trusted_hops {
+    ($this:expr, $trusted:expr, $min_trusted_hops:expr) => {
+        $crate::shared::find_ip_after_trusted_hops($crate::shared::chain_nodes_rev!($this), $trusted, $min_trusted_hops)
+    }
+}
+
+macro_rules! impl_forwarded_xff_consistent {
+    ($this:expr, $filter:expr) => {{
+        let forwarded = $this.get_all(FORWARDED)
+                             .into_iter()
+                             .rev()
+                             .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_forwarded_for_rev(header));
+        let x_forwarded = $this.get_all(X_FORWARDED_FOR)
+                               .into_iter()
+                               .rev()
+                               .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_x_forwarded_for_rev(header));
+        //Nothing to compare, unless both headers provide IP
+        let forwarded = $crate::shared::find_filtered_ip(forwarded, $filter)??;
+        let x_forwarded = $crate::shared::find_filtered_ip(x_forwarded, $filter)??;
+        Some(forwarded == x_forwarded)
+    }}
+}
+
+macro_rules! impl_forwarded_for_nodes {
+    ($this:expr) => {
+        $this.get_all(FORWARDED)
+             .into_iter()
+             .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_forwarded_for(header))
+    }
+}
+
+macro_rules! impl_forwarded_for_nodes_rev {
+    ($this:expr) => {
+        $this.get_all(FORWARDED)
+             .into_iter()
+             .rev()
+             .filter_map(|header| header.to_str().ok()).flat_map(|header| parse_forwarded_for_rev(header))
+    }
+}
+
+macro_rules! impl_extract_effective_scheme {
+    ($this:expr) => {{
+        let forwarded = $this.get_all(FORWARDED)
+                             .into_iter()
+                             .filter_map(|header| header.to_str().ok())
+                             .flat_map(|header| forwarded::parse_forwarded(header).flatten())
+                             .find_map(|value| match value {
+                                 forwarded::ForwardedValue::Protocol(proto) => Some(proto.trim_matches('"')),
+                                 _ => None,
+                             });
+        let scheme = match forwarded {
+            Some(proto) => proto,
+            None => $this.get(X_FORWARDED_PROTO)?.to_str().ok()?.split(',').next()?.trim(),
+        };
+
+        if scheme.eq_ignore_ascii_case("http") || scheme.eq_ignore_ascii_case("https") {
+            Some(scheme)
+        } else {
+            None
+        }
+    }}
+}
+
+pub(crate) use chain_nodes;
+pub(crate) use chain_nodes_rev;
 pub(crate) use impl_extract_leftmost_forwarded_ip;
 pub(crate) use impl_extract_rightmost_forwarded_ip;
 pub(crate) use impl_extract_filtered_forwarded_ip;
+pub(crate) use impl_extract_ip_from;
+pub(crate) use impl_extract_filtered_ip_from;
+pub(crate) use impl_extract_nearest_proxy_ip;
+pub(crate) use impl_extract_first_trusted_forwarded_ip;
+pub(crate) use impl_extract_client_ip_trusted_hops;
+pub(crate) use impl_resolve_client_ip_by;
+pub(crate) use impl_extract_filtered_forwarded_ip_prefer_v4;
+pub(crate) use impl_extract_filtered_forwarded_ip_bounded;
+pub(crate) use impl_extract_rightmost_by_ip;
+pub(crate) use impl_extract_first_public_forwarded_ip;
+pub(crate) use impl_extract_last_public_forwarded_ip;
+pub(crate) use impl_extract_client_ip_with_depth;
+pub(crate) use impl_extract_client_ip_leftmost_trusted;
+pub(crate) use impl_extract_filtered_leftmost_forwarded_ip;
+pub(crate) use impl_forwarded_xff_consistent;
+pub(crate) use impl_extract_effective_scheme;
+pub(crate) use impl_forwarded_for_nodes;
+pub(crate) use impl_forwarded_for_nodes_rev;
+#[cfg(feature = "smallvec")]
+pub(crate) use impl_extract_chain;
+pub(crate) use impl_extract_filtered_forwarded_ip_with;
+#[cfg(feature = "alloc")]
+pub(crate) use impl_explain_filtered_forwarded_ip;
+#[cfg(feature = "alloc")]
+pub(crate) use impl_collect_all_ips_with_source;
+#[cfg(feature = "alloc")]
+pub(crate) use impl_distinct_chain_ips;
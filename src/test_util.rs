@@ -0,0 +1,39 @@
+//! Utilities to build synthetic headers for testing
+//!
+//! Helpers panic on invalid input, as they are intended to be used in tests only.
+
+use core::fmt::Write;
+use core::net::IpAddr;
+
+use alloc::string::String;
+
+use crate::http::http_ext;
+use http_ext::header::{HeaderMap, HeaderValue, FORWARDED};
+
+const X_FORWARDED_FOR: http_ext::header::HeaderName = http_ext::header::HeaderName::from_static("x-forwarded-for");
+
+///Creates `HeaderMap` with every element of `entries` appended as separate `Forwarded` header line
+///
+///E.g. `build_forwarded(&["for=192.0.2.43", "for=198.51.100.17;proto=https"])`
+pub fn build_forwarded(entries: &[&str]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    for entry in entries {
+        headers.append(FORWARDED, HeaderValue::from_str(entry).expect("valid Forwarded entry"));
+    }
+    headers
+}
+
+///Creates `HeaderMap` with single `X-Forwarded-For` header, listing `ips` from left to right
+pub fn build_xff(ips: &[IpAddr]) -> HeaderMap {
+    let mut value = String::new();
+    for (idx, ip) in ips.iter().enumerate() {
+        if idx > 0 {
+            value.push_str(", ");
+        }
+        let _ = write!(value, "{ip}");
+    }
+
+    let mut headers = HeaderMap::new();
+    headers.insert(X_FORWARDED_FOR, HeaderValue::try_from(value).expect("valid X-Forwarded-For"));
+    headers
+}
@@ -1,4 +1,7 @@
 //! Tonic 0.14 extension module
+//!
+//! Note that only ASCII metadata is inspected by extraction methods, binary values (keys with `-bin` suffix) are ignored.
+//! Use [MetadataMapClientIp::extract_ip_from_binary] to read IP from binary metadata.
 
 use core::fmt;
 use core::net::IpAddr;
@@ -8,10 +11,16 @@ pub use tonic::metadata::MetadataMap;
 
 use crate::forwarded::{self, parse_forwarded_for, parse_forwarded_for_rev, parse_x_forwarded_for, parse_x_forwarded_for_rev};
 use crate::filter::Filter;
+use crate::extract::{ExtractConfig, HopKind};
+#[cfg(feature = "alloc")]
+use crate::extract::{HeaderSource, Resolution};
 use crate::shared::FALLBACK_STR;
 
 const FORWARDED: &str = "forwarded";
 const X_FORWARDED_FOR: &str = "x-forwarded-for";
+const X_FORWARDED_PROTO: &str = "x-forwarded-proto";
+const X_CLIENT_IP: &str = "x-client-ip";
+const X_REAL_IP: &str = "x-real-ip";
 
 ///FMT formatter for header values
 pub struct MetadataValueFmt<'a>(tonic::metadata::GetAll<'a, tonic::metadata::Ascii>);
@@ -30,20 +39,23 @@ impl fmt::Debug for MetadataValueFmt<'_> {
     }
 }
 
-impl fmt::Display for MetadataValueFmt<'_> {
-    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl MetadataValueFmt<'_> {
+    ///Writes comma separated values directly into `out`, without intermediate allocation
+    ///
+    ///This is the same output as `Display` produces
+    pub fn write_to<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
         let mut headers = self.0.iter();
         if let Some(header) = headers.next() {
             match header.to_str() {
-                Ok(header) => fmt.write_str(header)?,
-                Err(_) => fmt.write_str(FALLBACK_STR)?,
+                Ok(header) => out.write_str(header)?,
+                Err(_) => out.write_str(FALLBACK_STR)?,
             }
 
             for header in headers {
-                fmt.write_str(" ,")?;
+                out.write_str(", ")?;
                 match header.to_str() {
-                    Ok(header) => fmt.write_str(header)?,
-                    Err(_) => fmt.write_str(FALLBACK_STR)?,
+                    Ok(header) => out.write_str(header)?,
+                    Err(_) => out.write_str(FALLBACK_STR)?,
                 }
             }
         }
@@ -52,10 +64,23 @@ impl fmt::Display for MetadataValueFmt<'_> {
     }
 }
 
+impl fmt::Display for MetadataValueFmt<'_> {
+    #[inline(always)]
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(fmt)
+    }
+}
+
 ///`MetadataMap` extension trait
-pub trait MetadataMapClientIp {
+///
+///This trait is sealed and cannot be implemented outside of this crate
+pub trait MetadataMapClientIp: crate::shared::Sealed {
     ///Retrieves FMT formatter for header value matching provided `key`
     fn get_header_value_fmt(&self, key: &str) -> MetadataValueFmt<'_>;
+    ///Returns iterator over `for` nodes of every `Forwarded` header line, from left to right
+    fn forwarded_for_nodes(&self) -> impl Iterator<Item = forwarded::ForwardedNode<'_>>;
+    ///Returns iterator over `for` nodes of every `Forwarded` header line, from right to left
+    fn forwarded_for_nodes_rev(&self) -> impl Iterator<Item = forwarded::ForwardedNode<'_>>;
     ///Extracts leftmost client IP with no assumption.
     ///
     ///Note that this is generally not reliable as your client might be behind proxy
@@ -67,22 +92,198 @@ pub trait MetadataMapClientIp {
     ///
     ///Returns `None` if IP is not provided or obfuscated
     fn extract_rightmost_forwarded_ip(&self) -> Option<IpAddr>;
+    ///Extracts IP of the nearest proxy, which is rightmost node across all header values.
+    ///
+    ///This node is the hop that sent request to you, hence no filtering is applied.
+    ///Use it together with `extract_filtered_forwarded_ip` to get both proxy and client IPs.
+    ///
+    ///Returns `None` if IP is not provided or obfuscated
+    fn extract_nearest_proxy_ip(&self) -> Option<IpAddr>;
     ///Extracts client ip taking rightmost, after filtering out any IP matching `filter`
     ///
     ///Returns `None` if IP is not provided or obfuscated
-    fn extract_filtered_forwarded_ip(&self, filter: &impl Filter) -> Option<IpAddr>;
+    fn extract_filtered_forwarded_ip(&self, filter: &(impl Filter + ?Sized)) -> Option<IpAddr>;
     ///Extracts client ip taking rightmost, after filtering out any IP matching `filter` after skipping `skip` amount of IPs
     ///
     ///Returns `None` if IP is not provided or obfuscated
-    fn extract_filtered_forwarded_ip_after(&self, skip: usize, filter: &impl Filter) -> Option<IpAddr>;
+    fn extract_filtered_forwarded_ip_after(&self, skip: usize, filter: &(impl Filter + ?Sized)) -> Option<IpAddr>;
+    ///Extracts leftmost valid IP, scanning every header line
+    ///
+    ///# Security
+    ///
+    ///Leftmost IP is controlled by client and can be trivially spoofed.
+    ///This method is only safe when your service is reachable exclusively through single trusted proxy,
+    ///which always prepends real client IP, overwriting anything sent by client.
+    ///
+    ///Obfuscated and unknown nodes are skipped. `Forwarded` header is used if present, otherwise `X-Forwarded-For`.
+    ///
+    ///Returns `None` if there is no IP
+    fn extract_client_ip_leftmost_trusted(&self) -> Option<IpAddr>;
+    ///Extracts leftmost globally routable IP, as determined by [is_global](crate::filter::is_global)
+    ///
+    ///This heuristic is useful when you cannot enumerate your proxies, but note that client can still spoof public IP.
+    ///
+    ///Nodes without IP are skipped. `Forwarded` header is used if present, otherwise `X-Forwarded-For`.
+    ///
+    ///Returns `None` if there is no public IP
+    fn extract_first_public_forwarded_ip(&self) -> Option<IpAddr>;
+    ///Extracts rightmost globally routable IP, as determined by [is_global](crate::filter::is_global)
+    ///
+    ///This is the last public hop before request entered private network.
+    ///
+    ///Nodes without IP are skipped. `Forwarded` header is used if present, otherwise `X-Forwarded-For`.
+    ///
+    ///Returns `None` if there is no public IP
+    fn extract_last_public_forwarded_ip(&self) -> Option<IpAddr>;
+    ///Extracts client ip taking leftmost, after filtering out any IP matching `filter`
+    ///
+    ///This is useful to skip private addresses which clients may prepend to the chain.
+    ///
+    ///Returns `None` if IP is not provided or obfuscated
+    fn extract_filtered_leftmost_forwarded_ip(&self, filter: &(impl Filter + ?Sized)) -> Option<IpAddr>;
+    ///Extracts IP of the rightmost `by` node of the `Forwarded` header
+    ///
+    ///`by` node identifies interface where proxy received request.
+    ///
+    ///Returns `None` if there is no `by` node or it is not IP address
+    fn extract_rightmost_by_ip(&self) -> Option<IpAddr>;
+    ///Extracts client ip taking rightmost, after filtering out any IP matching `filter`, limiting number of nodes in the chain
+    ///
+    ///This guards against resource exhaustion by client stuffing header with excessive number of nodes.
+    ///
+    ///Returns `None` if chain has more than `max_nodes` nodes, or IP is not provided or obfuscated
+    fn extract_filtered_forwarded_ip_bounded(&self, filter: &(impl Filter + ?Sized), max_nodes: usize) -> Option<IpAddr>;
+    ///Extracts client ip taking rightmost, after filtering out any IP matching `filter`, preferring IPv4 for dual-stack hops
+    ///
+    ///Dual-stack proxies may specify both IPv4 and IPv6 of the client within single `Forwarded` entry (e.g. `for=192.0.2.1;for="[2001:db8::1]"`).
+    ///Entries are inspected from right to left, skipping entry if all its IPs match `filter`.
+    ///Within first entry, which has IP not matching `filter`, IPv4 is selected if present, otherwise IPv6.
+    ///
+    ///`X-Forwarded-For` has no notion of entry, hence this method behaves the same as `extract_filtered_forwarded_ip` when it is used.
+    ///
+    ///Returns `None` if IP is not provided or obfuscated
+    fn extract_filtered_forwarded_ip_prefer_v4(&self, filter: &(impl Filter + ?Sized)) -> Option<IpAddr>;
+    ///Resolves client ip by classifying each hop with `classify`, starting from rightmost
+    ///
+    ///[Trusted](HopKind::Trusted) hops are skipped, first [Client](HopKind::Client) hop is returned,
+    ///while [Reject](HopKind::Reject) aborts resolution.
+    ///This allows custom per-hop logic (e.g. lookup in external allow-list), beyond CIDR filtering.
+    ///
+    ///Returns `None` if resolution is rejected, or IP is not provided or obfuscated
+    fn resolve_client_ip_by(&self, classify: impl Fn(IpAddr) -> HopKind) -> Option<IpAddr>;
+    ///Extracts rightmost IP matching `trusted` filter, skipping any IP that doesn't match
+    ///
+    ///This is inverse of `extract_filtered_forwarded_ip`, returning IP of the last trusted hop (e.g. your load balancer)
+    ///
+    ///Returns `None` if no IP matches or obfuscated node is encountered
+    fn extract_first_trusted_forwarded_ip(&self, trusted: &(impl Filter + ?Sized)) -> Option<IpAddr>;
+    ///Extracts client ip taking rightmost, after skipping IPs matching `trusted`, requiring at least `min_trusted_hops` of them
+    ///
+    ///If fewer trusted hops are present than expected, request did not pass through your proxies as expected and
+    ///header is possibly spoofed.
+    ///
+    ///Returns `None` if there are not enough trusted hops, or IP is not provided or obfuscated
+    fn extract_client_ip_trusted_hops(&self, trusted: &(impl Filter + ?Sized), min_trusted_hops: usize) -> Option<IpAddr>;
+    ///Extracts client ip taking rightmost, after filtering out any IP matching `filter`, together with number of filtered hops
+    ///
+    ///Depth is number of trusted proxies in front of the client IP.
+    ///
+    ///Returns `None` if IP is not provided or obfuscated
+    fn extract_client_ip_with_depth(&self, filter: &(impl Filter + ?Sized)) -> Option<(IpAddr, usize)>;
+    ///Checks whether `Forwarded` and `X-Forwarded-For` resolve to the same client IP, after filtering out any IP matching `filter`
+    ///
+    ///Mismatch may indicate misconfiguration of proxies or tampering with headers.
+    ///
+    ///Returns `None` if either header is absent or provides no IP (e.g. only obfuscated or `unknown` nodes)
+    fn forwarded_xff_consistent(&self, filter: &(impl Filter + ?Sized)) -> Option<bool>;
+    ///Extracts scheme used by client, suitable for building redirect URLs
+    ///
+    ///Prefers `proto` parameter of the `Forwarded` header, falling back to first value of `X-Forwarded-Proto`.
+    ///
+    ///Returns `None` if scheme is neither `http` nor `https`
+    fn extract_effective_scheme(&self) -> Option<&str>;
+    ///Extracts client ip taking rightmost, after filtering out any IP matching `filter`, inspecting headers according to `config`
+    ///
+    ///Unlike `extract_filtered_forwarded_ip`, this method allows to customize which headers are trusted and in what order.
+    ///
+    ///Returns `None` if IP is not provided or obfuscated
+    fn extract_filtered_forwarded_ip_with(&self, filter: &(impl Filter + ?Sized), config: &ExtractConfig<'_>) -> Option<IpAddr>;
+    #[cfg(feature = "alloc")]
+    ///Performs the same resolution as `extract_filtered_forwarded_ip`, recording every inspected node
+    ///
+    ///This is intended as debugging aid, when you need to find out why particular IP is selected
+    fn explain_filtered_forwarded_ip(&self, filter: &(impl Filter + ?Sized)) -> Resolution<'_>;
+    #[cfg(feature = "alloc")]
+    ///Collects every IP found within `Forwarded`, `X-Forwarded-For` and `X-Real-IP` headers, paired with header it comes from
+    ///
+    ///IPs are ordered by header in listed order, and left to right within each header.
+    ///Nodes without IP address are skipped.
+    ///
+    ///This performs no filtering, and is intended for logging and auditing purposes
+    fn collect_all_ips_with_source(&self) -> alloc::vec::Vec<(IpAddr, HeaderSource)>;
+    #[cfg(feature = "alloc")]
+    ///Collects unique IPs found anywhere within `Forwarded` and `X-Forwarded-For` headers
+    ///
+    ///Result is sorted and contains no duplicates. Nodes without IP address are skipped.
+    fn distinct_chain_ips(&self) -> alloc::vec::Vec<IpAddr>;
+    #[cfg(feature = "smallvec")]
+    ///Collects all IPs of the proxy chain in order from left to right
+    ///
+    ///`Forwarded` header is used if present, otherwise `X-Forwarded-For`. Nodes without IP address are skipped.
+    ///
+    ///Up to 4 IPs are stored inline, avoiding heap allocation for common case
+    fn extract_chain_smallvec(&self) -> smallvec::SmallVec<[IpAddr; 4]>;
+    ///Extracts IP from header `key`, assuming its value is a single IP address
+    ///
+    ///This is intended for nonstandard headers like `X-Client-IP` or `X-Forwarded`, which are set by some legacy appliances.
+    ///If header is present multiple times, the last value is used.
+    ///
+    ///Returns `None` if header is missing or its value is not IP address
+    ///
+    ///Binary metadata is not visible to this method, use `extract_ip_from_binary` instead
+    fn extract_ip_from(&self, key: &str) -> Option<IpAddr>;
+    ///Extracts IP from binary metadata `key`, which must have `-bin` suffix
+    ///
+    ///Decoded bytes are expected to be textual representation of IP address.
+    ///If metadata is present multiple times, the last value is used.
+    ///
+    ///Returns `None` if metadata is missing, its value is not UTF-8 or is not IP address
+    fn extract_ip_from_binary(&self, key: &str) -> Option<IpAddr>;
+    ///Extracts client ip from header `key`, assuming it contains comma separated list of IPs in `X-Forwarded-For` format
+    ///
+    ///Takes rightmost IP, after filtering out any IP matching `filter`.
+    ///
+    ///Returns `None` if header is missing, or IP is not provided or obfuscated
+    fn extract_filtered_ip_from(&self, key: &str, filter: &(impl Filter + ?Sized)) -> Option<IpAddr>;
+    #[inline(always)]
+    ///Extracts IP from nonstandard `X-Client-IP` header
+    ///
+    ///This header is not standard and should be only trusted if your proxy sets it.
+    ///
+    ///Returns `None` if IP is not provided or invalid
+    fn extract_x_client_ip(&self) -> Option<IpAddr> {
+        self.extract_ip_from(X_CLIENT_IP)
+    }
 }
 
+impl crate::shared::Sealed for MetadataMap {}
+
 impl MetadataMapClientIp for MetadataMap {
     #[inline(always)]
     fn get_header_value_fmt(&self, key: &str) -> MetadataValueFmt<'_> {
         MetadataValueFmt(self.get_all(key))
     }
 
+    #[inline(always)]
+    fn forwarded_for_nodes(&self) -> impl Iterator<Item = forwarded::ForwardedNode<'_>> {
+        crate::shared::impl_forwarded_for_nodes!(self)
+    }
+
+    #[inline(always)]
+    fn forwarded_for_nodes_rev(&self) -> impl Iterator<Item = forwarded::ForwardedNode<'_>> {
+        crate::shared::impl_forwarded_for_nodes_rev!(self)
+    }
+
     #[inline(always)]
     fn extract_leftmost_forwarded_ip(&self) -> Option<IpAddr> {
         crate::shared::impl_extract_leftmost_forwarded_ip!(self)
@@ -93,12 +294,108 @@ impl MetadataMapClientIp for MetadataMap {
         crate::shared::impl_extract_rightmost_forwarded_ip!(self)
     }
 
+    fn extract_nearest_proxy_ip(&self) -> Option<IpAddr> {
+        crate::shared::impl_extract_nearest_proxy_ip!(self)
+    }
+
     #[inline(always)]
-    fn extract_filtered_forwarded_ip(&self, filter: &impl Filter) -> Option<IpAddr> {
+    fn extract_filtered_forwarded_ip(&self, filter: &(impl Filter + ?Sized)) -> Option<IpAddr> {
         self.extract_filtered_forwarded_ip_after(0, filter)
     }
 
-    fn extract_filtered_forwarded_ip_after(&self, skip: usize, filter: &impl Filter) -> Option<IpAddr> {
+    fn extract_filtered_forwarded_ip_after(&self, skip: usize, filter: &(impl Filter + ?Sized)) -> Option<IpAddr> {
         crate::shared::impl_extract_filtered_forwarded_ip!(self, filter, skip)
     }
+
+    #[inline(always)]
+    fn extract_client_ip_leftmost_trusted(&self) -> Option<IpAddr> {
+        crate::shared::impl_extract_client_ip_leftmost_trusted!(self)
+    }
+
+    fn extract_first_public_forwarded_ip(&self) -> Option<IpAddr> {
+        crate::shared::impl_extract_first_public_forwarded_ip!(self)
+    }
+
+    fn extract_last_public_forwarded_ip(&self) -> Option<IpAddr> {
+        crate::shared::impl_extract_last_public_forwarded_ip!(self)
+    }
+
+    fn extract_filtered_leftmost_forwarded_ip(&self, filter: &(impl Filter + ?Sized)) -> Option<IpAddr> {
+        crate::shared::impl_extract_filtered_leftmost_forwarded_ip!(self, filter)
+    }
+
+    fn extract_filtered_forwarded_ip_bounded(&self, filter: &(impl Filter + ?Sized), max_nodes: usize) -> Option<IpAddr> {
+        crate::shared::impl_extract_filtered_forwarded_ip_bounded!(self, filter, max_nodes)
+    }
+
+    fn extract_filtered_forwarded_ip_prefer_v4(&self, filter: &(impl Filter + ?Sized)) -> Option<IpAddr> {
+        crate::shared::impl_extract_filtered_forwarded_ip_prefer_v4!(self, filter)
+    }
+
+    fn resolve_client_ip_by(&self, classify: impl Fn(IpAddr) -> HopKind) -> Option<IpAddr> {
+        crate::shared::impl_resolve_client_ip_by!(self, classify)
+    }
+
+    fn extract_rightmost_by_ip(&self) -> Option<IpAddr> {
+        crate::shared::impl_extract_rightmost_by_ip!(self)
+    }
+
+    fn extract_first_trusted_forwarded_ip(&self, trusted: &(impl Filter + ?Sized)) -> Option<IpAddr> {
+        crate::shared::impl_extract_first_trusted_forwarded_ip!(self, trusted)
+    }
+
+    fn extract_client_ip_trusted_hops(&self, trusted: &(impl Filter + ?Sized), min_trusted_hops: usize) -> Option<IpAddr> {
+        crate::shared::impl_extract_client_ip_trusted_hops!(self, trusted, min_trusted_hops)
+    }
+
+    fn extract_client_ip_with_depth(&self, filter: &(impl Filter + ?Sized)) -> Option<(IpAddr, usize)> {
+        crate::shared::impl_extract_client_ip_with_depth!(self, filter)
+    }
+
+    fn forwarded_xff_consistent(&self, filter: &(impl Filter + ?Sized)) -> Option<bool> {
+        crate::shared::impl_forwarded_xff_consistent!(self, filter)
+    }
+
+    fn extract_effective_scheme(&self) -> Option<&str> {
+        crate::shared::impl_extract_effective_scheme!(self)
+    }
+
+    fn extract_filtered_forwarded_ip_with(&self, filter: &(impl Filter + ?Sized), config: &ExtractConfig<'_>) -> Option<IpAddr> {
+        crate::shared::impl_extract_filtered_forwarded_ip_with!(self, filter, config)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn explain_filtered_forwarded_ip(&self, filter: &(impl Filter + ?Sized)) -> Resolution<'_> {
+        crate::shared::impl_explain_filtered_forwarded_ip!(self, filter)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn collect_all_ips_with_source(&self) -> alloc::vec::Vec<(IpAddr, HeaderSource)> {
+        crate::shared::impl_collect_all_ips_with_source!(self)
+    }
+
+    #[cfg(feature = "alloc")]
+    fn distinct_chain_ips(&self) -> alloc::vec::Vec<IpAddr> {
+        crate::shared::impl_distinct_chain_ips!(self)
+    }
+
+    #[cfg(feature = "smallvec")]
+    fn extract_chain_smallvec(&self) -> smallvec::SmallVec<[IpAddr; 4]> {
+        crate::shared::impl_extract_chain!(self)
+    }
+
+    #[inline(always)]
+    fn extract_ip_from(&self, key: &str) -> Option<IpAddr> {
+        crate::shared::impl_extract_ip_from!(self, key)
+    }
+
+    fn extract_filtered_ip_from(&self, key: &str, filter: &(impl Filter + ?Sized)) -> Option<IpAddr> {
+        crate::shared::impl_extract_filtered_ip_from!(self, key, filter)
+    }
+
+    fn extract_ip_from_binary(&self, key: &str) -> Option<IpAddr> {
+        let value = self.get_all_bin(key).into_iter().next_back()?.to_bytes().ok()?;
+        let value = core::str::from_utf8(&value).ok()?;
+        forwarded::ForwardedNode::parse_x_node(value).ip()
+    }
 }
@@ -0,0 +1,114 @@
+use http_ip::axum08::{ClientIp, ClientIpRejection, HeaderOnlyClientIp, RequiredClientIp};
+use http_ip::axum08::extract::{ConnectInfo, FromRequestParts};
+use http_ip::axum08::http::{Request, StatusCode};
+use http_ip::axum08::response::IntoResponse;
+
+use core::future::Future;
+use core::net::{IpAddr, SocketAddr};
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+
+const FORWARDED: &str = "forwarded";
+
+fn block_on<F: Future>(fut: F) -> F::Output {
+    let mut fut = pin!(fut);
+    let mut ctx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(result) = fut.as_mut().poll(&mut ctx) {
+            return result;
+        }
+    }
+}
+
+#[test]
+fn should_reject_missing_ip_with_configured_status() {
+    let (mut parts, _) = Request::new(()).into_parts();
+
+    let ip = block_on(ClientIp::<()>::from_request_parts(&mut parts, &())).expect("to not fail");
+    assert!(ip.into_inner().is_none());
+
+    let rejection = block_on(RequiredClientIp::<()>::from_request_parts(&mut parts, &())).expect_err("to fail");
+    assert_eq!(rejection, ClientIpRejection::default());
+    assert_eq!(rejection.into_response().status(), StatusCode::BAD_REQUEST);
+
+    let rejection = block_on(RequiredClientIp::<(), 403>::from_request_parts(&mut parts, &())).expect_err("to fail");
+    assert_eq!(rejection.status(), StatusCode::FORBIDDEN);
+    assert_eq!(rejection.into_response().status(), StatusCode::FORBIDDEN);
+
+    let rejection = ClientIpRejection::new().with_status(StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(rejection.into_response().status(), StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[test]
+fn should_extract_required_ip() {
+    let request = Request::builder().header(FORWARDED, "For=203.0.113.195").body(()).unwrap();
+    let (mut parts, _) = request.into_parts();
+
+    let expected_ip: IpAddr = "203.0.113.195".parse().unwrap();
+    let ip = block_on(RequiredClientIp::<(), 403>::from_request_parts(&mut parts, &())).expect("to get ip");
+    assert_eq!(ip.into_inner(), expected_ip);
+}
+
+#[test]
+fn should_extract_header_only_ip() {
+    let socket_addr: SocketAddr = "192.0.2.1:4711".parse().unwrap();
+    let mut request = Request::new(());
+    request.extensions_mut().insert(ConnectInfo(socket_addr));
+    let (mut parts, _) = request.into_parts();
+
+    let ip = block_on(ClientIp::<()>::from_request_parts(&mut parts, &())).expect("to not fail");
+    assert_eq!(ip.into_inner(), Some(socket_addr.ip()));
+    let ip = block_on(HeaderOnlyClientIp::<()>::from_request_parts(&mut parts, &())).expect("to not fail");
+    assert_eq!(ip.into_inner(), None);
+
+    parts.headers.insert(FORWARDED, "For=203.0.113.195".parse().unwrap());
+    let expected_ip: IpAddr = "203.0.113.195".parse().unwrap();
+    let ip = block_on(HeaderOnlyClientIp::<()>::from_request_parts(&mut parts, &())).expect("to not fail");
+    assert_eq!(ip.into_inner(), Some(expected_ip));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_extract_ip_with_runtime_selected_filter() {
+    use http_ip::axum08::extract::FromRef;
+    use http_ip::filter::{Cidr, DynFilter, FilterExt};
+
+    #[derive(Clone)]
+    struct State {
+        filter: DynFilter,
+    }
+
+    impl FromRef<State> for DynFilter {
+        fn from_ref(state: &State) -> Self {
+            state.filter.clone()
+        }
+    }
+
+    let use_cidr = true;
+    let filter = if use_cidr {
+        DynFilter::new(Cidr::from_text("10.0.0.0/8").unwrap())
+    } else {
+        DynFilter::from(().boxed())
+    };
+    let state = State { filter };
+
+    let request = Request::builder().header(FORWARDED, "For=203.0.113.195, For=10.0.0.1").body(()).unwrap();
+    let (mut parts, _) = request.into_parts();
+
+    let expected_ip: IpAddr = "203.0.113.195".parse().unwrap();
+    let ip = block_on(ClientIp::<DynFilter>::from_request_parts(&mut parts, &state)).expect("to not fail");
+    assert_eq!(ip.into_inner(), Some(expected_ip));
+}
+
+#[test]
+fn should_extract_rightmost_ip_with_default_filter() {
+    let request = Request::builder().header(FORWARDED, "For=203.0.113.195, For=10.0.0.1").body(()).unwrap();
+    let (mut parts, _) = request.into_parts();
+
+    let expected_ip: IpAddr = "10.0.0.1".parse().unwrap();
+    let ip = block_on(ClientIp::<_>::from_request_parts(&mut parts, &())).expect("to not fail");
+    let ip: ClientIp = ip;
+    assert_eq!(ip.into_inner(), Some(expected_ip));
+    let ip: RequiredClientIp = block_on(RequiredClientIp::from_request_parts(&mut parts, &())).expect("to get ip");
+    assert_eq!(ip.into_inner(), expected_ip);
+}
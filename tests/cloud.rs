@@ -0,0 +1,108 @@
+use http_ip::filter::{Cidr, Filter};
+use http_ip::filter::cloud::{AwsIpRanges, AzureServiceTags, GcpIpRanges};
+
+const AWS_IP_RANGES: &str = r#"{
+  "syncToken": "1700000000",
+  "createDate": "2024-01-01-00-00-00",
+  "prefixes": [
+    {"ip_prefix": "3.5.140.0/22", "region": "ap-northeast-2", "service": "AMAZON", "network_border_group": "ap-northeast-2"},
+    {"ip_prefix": "13.32.0.0/15", "region": "GLOBAL", "service": "CLOUDFRONT", "network_border_group": "GLOBAL"},
+    {"ip_prefix": "15.177.0.0/18", "region": "us-east-1", "service": "ROUTE53_HEALTHCHECKS", "network_border_group": "us-east-1"}
+  ],
+  "ipv6_prefixes": [
+    {"ipv6_prefix": "2600:9000::/28", "region": "GLOBAL", "service": "CLOUDFRONT", "network_border_group": "GLOBAL"},
+    {"ipv6_prefix": "2600:1f14::/35", "region": "us-west-2", "service": "EC2", "network_border_group": "us-west-2"}
+  ]
+}"#;
+
+#[test]
+fn should_parse_aws_ip_ranges() {
+    let ranges = AwsIpRanges::parse(AWS_IP_RANGES).expect("parse");
+    assert_eq!(ranges.prefixes().len(), 5);
+    assert_eq!(ranges.prefixes()[4].cidr, Cidr::from_text("2600:1f14::/35").unwrap());
+    assert_eq!(ranges.prefixes()[4].region, "us-west-2");
+
+    let cloudfront: Vec<Cidr> = ranges.cidrs(Some("CLOUDFRONT"), None).collect();
+    assert_eq!(cloudfront, http_ip::cidrs!["13.32.0.0/15", "2600:9000::/28"]);
+    let us_east: Vec<Cidr> = ranges.cidrs(None, Some("us-east-1")).collect();
+    assert_eq!(us_east, http_ip::cidrs!["15.177.0.0/18"]);
+    assert_eq!(ranges.cidrs(Some("EC2"), Some("GLOBAL")).count(), 0);
+    assert_eq!(ranges.cidrs(None, None).count(), 5);
+
+    let trie = ranges.to_trie(Some("CLOUDFRONT"), None);
+    assert!(trie.is_match("13.33.1.1".parse().unwrap()));
+    assert!(trie.is_match("2600:9000::1".parse().unwrap()));
+    assert!(!trie.is_match("3.5.140.1".parse().unwrap()));
+
+    assert!(AwsIpRanges::parse("{}").expect("parse").prefixes().is_empty());
+    assert!(AwsIpRanges::parse(r#"{"prefixes": [{"ip_prefix": "3.5.140.0/33", "region": "a", "service": "b"}]}"#).is_err());
+    assert!(AwsIpRanges::parse("[").is_err());
+}
+
+#[test]
+fn should_parse_gcp_ip_ranges() {
+    const CLOUD_JSON: &str = r#"{
+      "syncToken": "1700000000",
+      "creationTime": "2024-01-01T00:00:00.000000",
+      "prefixes": [
+        {"ipv4Prefix": "34.1.208.0/20", "service": "Google Cloud", "scope": "africa-south1"},
+        {"ipv4Prefix": "34.35.0.0/16", "service": "Google Cloud", "scope": "us-central1"},
+        {"ipv6Prefix": "2600:1900:8000::/44", "service": "Google Cloud", "scope": "us-central1"}
+      ]
+    }"#;
+    const GOOG_JSON: &str = r#"{"syncToken": "1", "creationTime": "2024", "prefixes": [{"ipv4Prefix": "8.8.4.0/24"}, {"ipv6Prefix": "2001:4860::/32"}]}"#;
+
+    let ranges = GcpIpRanges::parse(CLOUD_JSON).expect("parse");
+    assert_eq!(ranges.prefixes().len(), 3);
+    assert_eq!(ranges.prefixes()[0].service.as_deref(), Some("Google Cloud"));
+    let us_central: Vec<Cidr> = ranges.cidrs(Some("us-central1")).collect();
+    assert_eq!(us_central, http_ip::cidrs!["34.35.0.0/16", "2600:1900:8000::/44"]);
+    assert_eq!(ranges.cidrs(None).count(), 3);
+
+    let trie = ranges.to_trie(Some("africa-south1"));
+    assert!(trie.is_match("34.1.208.1".parse().unwrap()));
+    assert!(!trie.is_match("34.35.0.1".parse().unwrap()));
+
+    let ranges = GcpIpRanges::parse(GOOG_JSON).expect("parse");
+    assert!(ranges.prefixes()[0].scope.is_none());
+    assert_eq!(ranges.cidrs(Some("us-central1")).count(), 0);
+    let trie = ranges.to_trie(None);
+    assert!(trie.is_match("8.8.4.4".parse().unwrap()));
+    assert!(trie.is_match("2001:4860::8888".parse().unwrap()));
+}
+
+#[test]
+fn should_parse_azure_service_tags() {
+    const SERVICE_TAGS: &str = r#"{
+      "changeNumber": 300,
+      "cloud": "Public",
+      "values": [
+        {
+          "name": "AzureFrontDoor.Backend",
+          "id": "AzureFrontDoor.Backend",
+          "properties": {"changeNumber": 10, "region": "", "regionId": 0, "platform": "Azure", "systemService": "AzureFrontDoor", "addressPrefixes": ["13.73.248.8/29", "2603:1000:4::5e0/123"], "networkFeatures": ["API"]}
+        },
+        {
+          "name": "AzureCloud.westeurope",
+          "id": "AzureCloud.westeurope",
+          "properties": {"changeNumber": 20, "region": "westeurope", "regionId": 18, "platform": "Azure", "systemService": "", "addressPrefixes": ["13.69.0.0/17"]}
+        }
+      ]
+    }"#;
+
+    let tags = AzureServiceTags::parse(SERVICE_TAGS).expect("parse");
+    assert_eq!(tags.tags().len(), 2);
+    assert_eq!(tags.tags()[1].region, "westeurope");
+
+    let front_door: Vec<Cidr> = tags.cidrs(Some("AzureFrontDoor.Backend"), None).collect();
+    assert_eq!(front_door, http_ip::cidrs!["13.73.248.8/29", "2603:1000:4::5e0/123"]);
+    let west_europe: Vec<Cidr> = tags.cidrs(None, Some("westeurope")).collect();
+    assert_eq!(west_europe, http_ip::cidrs!["13.69.0.0/17"]);
+    assert_eq!(tags.cidrs(None, None).count(), 3);
+
+    let trie = tags.to_trie(Some("AzureFrontDoor.Backend"), None);
+    assert!(trie.is_match("13.73.248.9".parse().unwrap()));
+    assert!(!trie.is_match("13.69.0.1".parse().unwrap()));
+
+    assert!(AzureServiceTags::parse(r#"{"values": [{"name": "a", "properties": {"addressPrefixes": ["bad"]}}]}"#).is_err());
+}
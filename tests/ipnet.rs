@@ -0,0 +1,39 @@
+use http_ip::filter::{Cidr, Filter};
+use ipnet::{IpNet, Ipv4Net, Ipv6Net};
+
+use core::net::IpAddr;
+
+#[test]
+fn should_match_ipnet_filter() {
+    let net: IpNet = "10.0.0.0/8".parse().unwrap();
+    assert!(net.is_match("10.1.2.3".parse().unwrap()));
+    assert!(!net.is_match("11.1.2.3".parse().unwrap()));
+
+    let net: Ipv4Net = "192.168.0.0/16".parse().unwrap();
+    assert!(net.is_match("192.168.1.1".parse().unwrap()));
+    assert!(!net.is_match("::1".parse().unwrap()));
+
+    let net: Ipv6Net = "2001:db8::/32".parse().unwrap();
+    assert!(net.is_match("2001:db8::1".parse().unwrap()));
+    assert!(!net.is_match("10.0.0.1".parse().unwrap()));
+
+    let nets: [IpNet; 2] = ["10.0.0.0/8".parse().unwrap(), "2001:db8::/32".parse().unwrap()];
+    let nodes = http_ip::forwarded::parse_x_forwarded_for_rev("198.51.100.7, 2001:db8::1, 10.0.0.1");
+    assert_eq!(http_ip::find_next_ip_after_filter(nodes, &nets), Some("198.51.100.7".parse::<IpAddr>().unwrap()));
+}
+
+#[test]
+fn should_convert_between_ipnet_and_cidr() {
+    let net: IpNet = "10.1.2.3/8".parse().unwrap();
+    let cidr = Cidr::from(net);
+    assert_eq!(cidr, Cidr::from_text("10.0.0.0/8").unwrap());
+    assert_eq!(IpNet::from(cidr), "10.0.0.0/8".parse::<IpNet>().unwrap());
+
+    let net: Ipv6Net = "2001:db8::/32".parse().unwrap();
+    let cidr = Cidr::from(net);
+    assert_eq!(cidr, Cidr::from_text("2001:db8::/32").unwrap());
+    assert_eq!(IpNet::from(cidr), IpNet::V6(net));
+
+    let net: Ipv4Net = "127.0.0.1/32".parse().unwrap();
+    assert_eq!(Cidr::from(net), Cidr::from_text("127.0.0.1").unwrap());
+}
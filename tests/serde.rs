@@ -0,0 +1,40 @@
+use http_ip::filter::{Cidr, Filter, FilterConfig};
+
+use core::net::IpAddr;
+
+#[test]
+fn should_serialize_cidr_as_text() {
+    let cidr = Cidr::from_text("10.0.0.0/8").unwrap();
+    assert_eq!(serde_json::to_string(&cidr).unwrap(), "\"10.0.0.0/8\"");
+    let cidr = Cidr::from_text("2001:db8::/32").unwrap();
+    assert_eq!(serde_json::to_string(&cidr).unwrap(), "\"2001:db8::/32\"");
+
+    let cidrs: Vec<Cidr> = serde_json::from_str(r#"["10.0.0.0/8", "2001:db8::/32", "192.168.0.1"]"#).unwrap();
+    assert_eq!(cidrs, http_ip::cidrs!["10.0.0.0/8", "2001:db8::/32", "192.168.0.1/32"]);
+    assert!(serde_json::from_str::<Cidr>("\"10.0.0.0/33\"").is_err());
+    assert!(serde_json::from_str::<Cidr>("1").is_err());
+}
+
+#[test]
+fn should_deserialize_filter_config() {
+    let config: FilterConfig = serde_json::from_str("\"10.0.0.1\"").unwrap();
+    assert_eq!(config, FilterConfig::Ip("10.0.0.1".parse().unwrap()));
+    let filter = config.into_filter();
+    assert!(filter.is_match("10.0.0.1".parse().unwrap()));
+    assert!(!filter.is_match("10.0.0.2".parse().unwrap()));
+
+    let config: FilterConfig = serde_json::from_str("\"10.0.0.0/8\"").unwrap();
+    assert_eq!(config, FilterConfig::Cidr(Cidr::from_text("10.0.0.0/8").unwrap()));
+
+    let config: FilterConfig = serde_json::from_str(r#"["203.0.113.1", "192.168.0.0/16", ["2001:db8::/32"]]"#).unwrap();
+    let filter = config.into_filter();
+    for ip in ["203.0.113.1", "192.168.1.1", "2001:db8::1"] {
+        assert!(filter.is_match(ip.parse::<IpAddr>().unwrap()), "{ip}");
+    }
+    for ip in ["203.0.113.2", "10.0.0.1", "2001:db9::1"] {
+        assert!(!filter.is_match(ip.parse::<IpAddr>().unwrap()), "{ip}");
+    }
+
+    assert!(serde_json::from_str::<FilterConfig>("\"not ip\"").is_err());
+    assert!(serde_json::from_str::<FilterConfig>("{}").is_err());
+}
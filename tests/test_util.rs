@@ -0,0 +1,26 @@
+use http_ip::http::HeaderMapClientIp;
+use http_ip::filter::Cidr;
+use http_ip::test_util::{build_forwarded, build_xff};
+
+use core::net::IpAddr;
+
+#[test]
+fn should_build_forwarded_header_map() {
+    let filter = Cidr::from_text("10.0.0.0/24").unwrap();
+    let headers = build_forwarded(&["for=203.0.113.195", "for=\"[2001:db8::1]\",for=10.0.0.1"]);
+
+    assert_eq!(headers.extract_leftmost_forwarded_ip(), Some("203.0.113.195".parse().unwrap()));
+    assert_eq!(headers.extract_filtered_forwarded_ip(&filter), Some("2001:db8::1".parse().unwrap()));
+    assert_eq!(headers.get_all(http::header::FORWARDED).iter().count(), 2);
+}
+
+#[test]
+fn should_build_x_forwarded_for_header_map() {
+    let filter = Cidr::from_text("10.0.0.0/24").unwrap();
+    let ips: [IpAddr; 3] = ["203.0.113.195".parse().unwrap(), "2001:db8::1".parse().unwrap(), "10.0.0.1".parse().unwrap()];
+    let headers = build_xff(&ips);
+
+    assert_eq!(headers.extract_leftmost_forwarded_ip(), Some(ips[0]));
+    assert_eq!(headers.extract_rightmost_forwarded_ip(), Some(ips[2]));
+    assert_eq!(headers.extract_filtered_forwarded_ip(&filter), Some(ips[1]));
+}
@@ -203,3 +203,1742 @@ fn should_parse_forwarded_with_real_life_scenario() {
     let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &filter).expect("Find ip");
     assert_eq!(ip, EXPECTED_IP);
 }
+
+#[test]
+fn should_match_ip_family_specific_filters() {
+    use core::net::Ipv6Addr;
+    use http_ip::filter::Filter;
+
+    let filter = Ipv4Addr::new(10, 0, 0, 1);
+    assert!(filter.is_match(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
+    assert!(!filter.is_match(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))));
+    assert!(!filter.is_match(IpAddr::V6(Ipv4Addr::new(10, 0, 0, 1).to_ipv6_mapped())));
+
+    let filter = Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1);
+    assert!(filter.is_match(IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1))));
+    assert!(!filter.is_match(IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 2))));
+    assert!(!filter.is_match(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
+}
+
+struct FixedBuf {
+    buf: [u8; 128],
+    len: usize,
+}
+
+impl FixedBuf {
+    fn new() -> Self {
+        Self {
+            buf: [0; 128],
+            len: 0,
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        core::str::from_utf8(&self.buf[..self.len]).unwrap()
+    }
+}
+
+impl core::fmt::Write for FixedBuf {
+    fn write_str(&mut self, text: &str) -> core::fmt::Result {
+        let end = self.len + text.len();
+        if end > self.buf.len() {
+            return Err(core::fmt::Error);
+        }
+        self.buf[self.len..end].copy_from_slice(text.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+#[test]
+fn should_write_forwarded_entry() {
+    use http_ip::forwarded::write_forwarded_entry;
+
+    let by: IpAddr = "2001:db8:cafe::17".parse().unwrap();
+    let client: IpAddr = "192.0.2.60".parse().unwrap();
+
+    let mut out = FixedBuf::new();
+    write_forwarded_entry(&mut out, Some(ForwardedNode::Ip(by)), Some(ForwardedNode::Ip(client)), Some("example.com:8080"), Some("https")).expect("to write");
+    assert_eq!(out.as_str(), "by=\"[2001:db8:cafe::17]\";for=192.0.2.60;host=\"example.com:8080\";proto=https");
+
+    let mut ips = parse_forwarded(out.as_str());
+    let mut entry = ips.next().expect("have single entry");
+    assert_eq!(entry.next(), Some(ForwardedValue::By(ForwardedNode::Ip(by))));
+    assert_eq!(entry.next(), Some(ForwardedValue::For(ForwardedNode::Ip(client))));
+    assert!(ips.next().is_none());
+
+    let mut out = FixedBuf::new();
+    write_forwarded_entry(&mut out, None, Some(ForwardedNode::Unknown), None, None).expect("to write");
+    assert_eq!(out.as_str(), "for=unknown");
+
+    let mut out = FixedBuf::new();
+    write_forwarded_entry(&mut out, None, Some(ForwardedNode::Name("_hidden")), None, Some("http")).expect("to write");
+    assert_eq!(out.as_str(), "for=_hidden;proto=http");
+
+    let mut out = FixedBuf::new();
+    write_forwarded_entry(&mut out, None, None, Some("a\"b"), None).expect("to write");
+    assert_eq!(out.as_str(), "host=\"a\\\"b\"");
+}
+
+#[test]
+fn should_format_for_node() {
+    use core::fmt::Write;
+    use core::net::SocketAddr;
+    use http_ip::forwarded::{format_for_node, format_for_socket_addr};
+
+    let mut out = FixedBuf::new();
+    write!(out, "{}", format_for_node(ForwardedNode::Ip("192.0.2.1".parse().unwrap()))).expect("to write");
+    assert_eq!(out.as_str(), "for=192.0.2.1");
+
+    let mut out = FixedBuf::new();
+    write!(out, "{}", format_for_node(ForwardedNode::Ip("2001:db8::1".parse().unwrap()))).expect("to write");
+    assert_eq!(out.as_str(), "for=\"[2001:db8::1]\"");
+
+    let addr: SocketAddr = "[2001:db8::1]:4711".parse().unwrap();
+    let mut out = FixedBuf::new();
+    write!(out, "{}", format_for_socket_addr(addr)).expect("to write");
+    assert_eq!(out.as_str(), "for=\"[2001:db8::1]:4711\"");
+    let mut nodes = parse_forwarded_for(out.as_str());
+    assert_eq!(nodes.next(), Some(ForwardedNode::Ip(addr.ip())));
+
+    let addr: SocketAddr = "192.0.2.1:4711".parse().unwrap();
+    let mut out = FixedBuf::new();
+    write!(out, "{}", format_for_socket_addr(addr)).expect("to write");
+    assert_eq!(out.as_str(), "for=\"192.0.2.1:4711\"");
+}
+
+#[test]
+fn should_match_ip_set() {
+    use core::net::Ipv6Addr;
+    use http_ip::filter::{Filter, IpSet};
+
+    const SORTED: [IpAddr; 4] = [
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)),
+        IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)),
+        IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+    ];
+    const SORTED_SET: IpSet<[IpAddr; 4]> = IpSet::new_sorted(SORTED);
+    let unsorted = [SORTED[2], SORTED[0], SORTED[3], SORTED[1]];
+    let unsorted_set = IpSet::new(&unsorted);
+
+    for ip in SORTED {
+        assert!(SORTED_SET.is_match(ip), "Should match {ip}");
+        assert!(unsorted_set.is_match(ip), "Should match {ip}");
+    }
+
+    for ip in [IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)), IpAddr::V6(Ipv6Addr::LOCALHOST), IpAddr::V4(Ipv4Addr::new(192, 168, 0, 2))] {
+        assert!(!SORTED_SET.is_match(ip), "Should not match {ip}");
+        assert!(!unsorted_set.is_match(ip), "Should not match {ip}");
+    }
+}
+
+#[test]
+fn should_compare_forwarded_node_with_ip() {
+    let ip: IpAddr = "127.0.0.1".parse().unwrap();
+    let other_ip: IpAddr = "127.0.0.2".parse().unwrap();
+
+    assert_eq!(ForwardedNode::Ip(ip), ip);
+    assert_ne!(ForwardedNode::Ip(ip), other_ip);
+    assert_ne!(ForwardedNode::Name("127.0.0.1"), ip);
+    assert_ne!(ForwardedNode::Unknown, ip);
+}
+
+#[test]
+fn should_stay_exhausted_after_end() {
+    use core::iter::FusedIterator;
+    use http_ip::forwarded::ForwardedEntryIter;
+
+    fn assert_exhausted<I: FusedIterator>(mut iter: I) {
+        while iter.next().is_some() {}
+        for _ in 0..3 {
+            assert!(iter.next().is_none());
+        }
+    }
+
+    const FORWARDED: &str = "By=\"[2001:db8:cafe::17]:4711\";For=127.0.0.1,For=unknown,For=_hidden";
+    const IPS: &str = "203.0.113.195,2001:db8:85a3:8d3:1319:8a2e:370:7348,198.51.100.178";
+
+    assert_exhausted(parse_forwarded(FORWARDED));
+    assert_exhausted(parse_forwarded_rev(FORWARDED));
+    assert_exhausted(parse_forwarded_for(FORWARDED));
+    assert_exhausted(parse_forwarded_for_rev(FORWARDED));
+    assert_exhausted(parse_x_forwarded_for(IPS));
+    assert_exhausted(parse_x_forwarded_for_rev(IPS));
+    assert_exhausted(ForwardedEntryIter::parse_entry("By=\"[2001:db8:cafe::17]:4711\";For=127.0.0.1"));
+}
+
+#[test]
+fn should_provide_size_hint() {
+    const FORWARDED: &str = "By=\"[2001:db8:cafe::17]:4711\";For=127.0.0.1,For=unknown,For=_hidden";
+    const IPS: &str = "203.0.113.195,2001:db8:85a3:8d3:1319:8a2e:370:7348,198.51.100.178";
+
+    let mut ips = parse_x_forwarded_for(IPS);
+    assert_eq!(ips.size_hint(), (3, Some(3)));
+    assert_eq!(ips.len(), 3);
+    ips.next();
+    assert_eq!(ips.size_hint(), (2, Some(2)));
+    assert_eq!(parse_x_forwarded_for_rev(IPS).size_hint(), (3, Some(3)));
+
+    let mut ips = parse_forwarded_for(FORWARDED);
+    assert_eq!(ips.size_hint(), (0, Some(4)));
+    ips.next();
+    assert_eq!(ips.size_hint(), (0, Some(2)));
+    assert_eq!(parse_forwarded_for_rev(FORWARDED).size_hint(), (0, Some(4)));
+    assert_eq!(parse_forwarded_for(FORWARDED).count(), 3);
+}
+
+#[test]
+fn should_parse_x_forwarded_for_with_ports() {
+    const IPS: &str = "203.0.113.1:443, [2001:db8::1]:443,[2001:db8::2],203.0.113.2:port,[2001:db8::3]:";
+
+    let mut ips = parse_x_forwarded_for(IPS);
+    let expected_ip: IpAddr = "203.0.113.1".parse().unwrap();
+    assert_eq!(ips.next(), Some(ForwardedNode::Ip(expected_ip)));
+    let expected_ip: IpAddr = "2001:db8::1".parse().unwrap();
+    assert_eq!(ips.next(), Some(ForwardedNode::Ip(expected_ip)));
+    let expected_ip: IpAddr = "2001:db8::2".parse().unwrap();
+    assert_eq!(ips.next(), Some(ForwardedNode::Ip(expected_ip)));
+    assert_eq!(ips.next(), Some(ForwardedNode::Name("203.0.113.2:port")));
+    assert_eq!(ips.next(), Some(ForwardedNode::Name("[2001:db8::3]:")));
+    assert!(ips.next().is_none());
+
+    let expected_ip: IpAddr = "2001:db8::1".parse().unwrap();
+    assert_eq!(ForwardedNode::parse_x_node("2001:db8::1"), ForwardedNode::Ip(expected_ip));
+}
+
+#[test]
+fn should_not_partially_parse_malformed_bracketed_node() {
+    let expected_ip: IpAddr = "2001:db8::1".parse().unwrap();
+
+    assert_eq!(ForwardedNode::parse_node("[2001:db8::1];junk"), ForwardedNode::Name("[2001:db8::1];junk"));
+    assert_eq!(ForwardedNode::parse_node("\"[2001:db8::1]junk\""), ForwardedNode::Name("[2001:db8::1]junk"));
+    assert_eq!(ForwardedNode::parse_node("[2001:db8::1]:port"), ForwardedNode::Name("[2001:db8::1]:port"));
+    assert_eq!(ForwardedNode::parse_node("[2001:db8::1"), ForwardedNode::Name("2001:db8::1"));
+    assert_eq!(ForwardedNode::parse_node("[]"), ForwardedNode::Name(""));
+
+    assert_eq!(ForwardedNode::parse_node("[2001:db8::1]"), ForwardedNode::Ip(expected_ip));
+    assert_eq!(ForwardedNode::parse_node("\"[2001:db8::1]:4711\""), ForwardedNode::Ip(expected_ip));
+    assert_eq!(ForwardedNode::parse_node("\"[2001:db8::1]:_hidden\""), ForwardedNode::Ip(expected_ip));
+}
+
+#[test]
+fn should_check_cidr_membership_in_const_context() {
+    const CIDR: http_ip::filter::Cidr = match http_ip::filter::Cidr::from_text("10.0.0.0/24") {
+        Ok(cidr) => cidr,
+        Err(_) => panic!("I cannot fail"),
+    };
+    const _: () = assert!(CIDR.contains_const(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 37))));
+    const _: () = assert!(!CIDR.contains_const(IpAddr::V4(Ipv4Addr::new(10, 0, 1, 37))));
+    assert!(CIDR.contains_const(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 255))));
+}
+
+#[test]
+fn should_parse_hops() {
+    use http_ip::forwarded::{parse_hops, parse_hops_rev, ForwardedEntry};
+
+    const FORWARDED: &str = "for=192.0.2.43;proto=https,for=198.51.100.17;by=\"[2001:db8:cafe::17]:4711\";host=example.com,for=unknown";
+    let hop1 = ForwardedEntry {
+        for_: Some(ForwardedNode::Ip("192.0.2.43".parse().unwrap())),
+        proto: Some("https"),
+        ..ForwardedEntry::default()
+    };
+    let hop2 = ForwardedEntry {
+        by: Some(ForwardedNode::Ip("2001:db8:cafe::17".parse().unwrap())),
+        for_: Some(ForwardedNode::Ip("198.51.100.17".parse().unwrap())),
+        host: Some("example.com"),
+        proto: None,
+    };
+    let hop3 = ForwardedEntry {
+        for_: Some(ForwardedNode::Unknown),
+        ..ForwardedEntry::default()
+    };
+
+    let mut hops = parse_hops_rev(FORWARDED);
+    assert_eq!(hops.next(), Some(hop3));
+    assert_eq!(hops.next(), Some(hop2));
+    assert_eq!(hops.next(), Some(hop1));
+    assert!(hops.next().is_none());
+
+    let mut hops = parse_hops(FORWARDED);
+    assert_eq!(hops.next(), Some(hop1));
+    assert_eq!(hops.next(), Some(hop2));
+    assert_eq!(hops.next(), Some(hop3));
+    assert!(hops.next().is_none());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_match_cidr_trie_same_as_linear_scan() {
+    use core::net::Ipv6Addr;
+    use http_ip::filter::{self, Cidr, CidrTrie, Filter};
+
+    //xorshift to have reproducible input
+    fn next_random(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
+    }
+
+    fn random_ip(state: &mut u64) -> IpAddr {
+        if next_random(state) % 4 == 0 {
+            let bits = ((next_random(state) as u128) << 64) | next_random(state) as u128;
+            IpAddr::V6(Ipv6Addr::from_bits(bits))
+        } else {
+            IpAddr::V4(Ipv4Addr::from_bits(next_random(state) as u32))
+        }
+    }
+
+    let mut state = 0xdead_beef_u64;
+    let mut cidrs = Vec::new();
+    for _ in 0..200 {
+        let ip = random_ip(&mut state);
+        let prefix = match ip {
+            IpAddr::V4(_) => 12 + (next_random(&mut state) % 21) as u8,
+            IpAddr::V6(_) => 16 + (next_random(&mut state) % 113) as u8,
+        };
+        cidrs.push(Cidr::new(ip, prefix).expect("valid prefix"));
+    }
+
+    let trie = CidrTrie::from_cidrs(&cidrs);
+    let linear = filter::collection_or(cidrs.as_slice());
+
+    for cidr in cidrs.iter() {
+        assert!(trie.is_match(cidr.network_addr()), "{cidr} should match its network address");
+    }
+
+    let mut matches = 0;
+    for _ in 0..10_000 {
+        let ip = random_ip(&mut state);
+        let expected = linear.is_match(ip);
+        assert_eq!(trie.is_match(ip), expected, "Mismatch for {ip}");
+        matches += expected as usize;
+    }
+    assert!(matches > 0);
+
+    let trie = CidrTrie::from_cidrs(&[Cidr::from_text("10.0.0.0/8").unwrap(), Cidr::from_text("10.1.0.0/16").unwrap(), Cidr::from_text("2001:db8::/32").unwrap()]);
+    assert!(trie.is_match("10.1.2.3".parse().unwrap()));
+    assert!(trie.is_match("10.2.2.3".parse().unwrap()));
+    assert!(!trie.is_match("11.1.2.3".parse().unwrap()));
+    assert!(trie.is_match("2001:db8::1".parse().unwrap()));
+    assert!(!trie.is_match("2001:db9::1".parse().unwrap()));
+    assert!(!CidrTrie::new().is_match("10.1.2.3".parse().unwrap()));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_collect_cidr_trie_from_iterator() {
+    use http_ip::filter::{Cidr, CidrTrie, Filter};
+
+    let mut trie: CidrTrie = (0..=255u8).map(|idx| Cidr::new(IpAddr::V4(Ipv4Addr::new(10, idx, 0, 0)), 24).unwrap()).collect();
+    assert!(trie.is_match("10.0.0.1".parse().unwrap()));
+    assert!(trie.is_match("10.255.0.255".parse().unwrap()));
+    assert!(!trie.is_match("10.0.1.1".parse().unwrap()));
+
+    trie.extend([Cidr::from_text("2001:db8::/32").unwrap()]);
+    assert!(trie.is_match("2001:db8::1".parse().unwrap()));
+}
+
+#[test]
+fn should_parse_bare_ip_forwarded_only_in_lenient_mode() {
+    use http_ip::forwarded::{parse_forwarded_for_lenient, parse_forwarded_for_lenient_rev, ForwardedEntryIter};
+
+    const FORWARDED: &str = "203.0.113.1";
+    let expected_ip: IpAddr = "203.0.113.1".parse().unwrap();
+
+    assert!(parse_forwarded_for(FORWARDED).next().is_none());
+    assert!(parse_forwarded_for_rev(FORWARDED).next().is_none());
+    assert!(ForwardedEntryIter::parse_entry(FORWARDED).next().is_none());
+
+    let mut ips = parse_forwarded_for_lenient(FORWARDED);
+    assert_eq!(ips.next(), Some(ForwardedNode::Ip(expected_ip)));
+    assert!(ips.next().is_none());
+    let mut entry = ForwardedEntryIter::parse_entry_lenient(FORWARDED);
+    assert_eq!(entry.next(), Some(ForwardedValue::For(ForwardedNode::Ip(expected_ip))));
+    assert!(entry.next().is_none());
+
+    let mut ips = parse_forwarded_for_lenient_rev("For=10.0.0.1,203.0.113.1,garbage");
+    assert_eq!(ips.next(), Some(ForwardedNode::Ip(expected_ip)));
+    assert_eq!(ips.next(), Some(ForwardedNode::Ip("10.0.0.1".parse().unwrap())));
+    assert!(ips.next().is_none());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_match_runtime_filter_list() {
+    use http_ip::filter::{Cidr, Filter, FilterList};
+
+    let dyn_filter: &dyn Filter = &Cidr::from_text("10.0.0.0/24").unwrap();
+    assert!(dyn_filter.is_match("10.0.0.1".parse().unwrap()));
+
+    let mut filter = FilterList::new();
+    assert!(filter.is_empty());
+    assert!(!filter.is_match("10.0.0.1".parse().unwrap()));
+
+    filter.push(Cidr::from_text("10.0.0.0/24").unwrap()).push("192.168.0.1".parse::<IpAddr>().unwrap());
+    assert_eq!(filter.len(), 2);
+    assert!(filter.is_match("10.0.0.1".parse().unwrap()));
+    assert!(filter.is_match("10.0.0.255".parse().unwrap()));
+    assert!(filter.is_match("192.168.0.1".parse().unwrap()));
+    assert!(!filter.is_match("192.168.0.2".parse().unwrap()));
+    assert!(!filter.is_match("10.0.1.1".parse().unwrap()));
+
+    const FORWARDED: &str = "For=203.0.113.195,For=192.168.0.1,For=10.0.0.1";
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &filter).expect("Find ip");
+    assert_eq!(ip, "203.0.113.195".parse::<IpAddr>().unwrap());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_use_boxed_filter_in_extraction() {
+    use http_ip::filter::{Cidr, Filter};
+
+    const FORWARDED: &str = "For=203.0.113.195,For=10.0.0.1";
+
+    let use_cidr = true;
+    let filter: Box<dyn Filter> = if use_cidr {
+        Box::new(Cidr::from_text("10.0.0.0/24").unwrap())
+    } else {
+        Box::new(())
+    };
+
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), filter.as_ref()).expect("Find ip");
+    assert_eq!(ip, "203.0.113.195".parse::<IpAddr>().unwrap());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_dedup_cidrs() {
+    use http_ip::filter::{dedup_cidrs, Cidr};
+
+    let cidrs = [
+        Cidr::from_text("10.1.2.0/24").unwrap(),
+        Cidr::from_text("192.168.0.0/24").unwrap(),
+        Cidr::from_text("10.1.0.0/16").unwrap(),
+        Cidr::from_text("2001:db8::/32").unwrap(),
+        Cidr::from_text("192.168.0.0/24").unwrap(),
+        Cidr::from_text("10.1.255.255").unwrap(),
+        Cidr::from_text("172.16.0.0/12").unwrap(),
+    ];
+
+    let result = dedup_cidrs(&cidrs);
+    assert_eq!(result, [
+        Cidr::from_text("192.168.0.0/24").unwrap(),
+        Cidr::from_text("10.1.0.0/16").unwrap(),
+        Cidr::from_text("2001:db8::/32").unwrap(),
+        Cidr::from_text("172.16.0.0/12").unwrap(),
+    ]);
+
+    assert!(cidrs[2].contains_cidr(&cidrs[0]));
+    assert!(!cidrs[0].contains_cidr(&cidrs[2]));
+    assert!(!cidrs[2].contains_cidr(&cidrs[3]));
+    assert!(dedup_cidrs(&[]).is_empty());
+}
+
+#[test]
+fn should_parse_bare_ip_as_host_cidr() {
+    use http_ip::filter::{Cidr, Filter};
+
+    let host = Cidr::from_text("10.0.0.1").expect("to parse bare ipv4");
+    assert_eq!(host.prefix(), 32);
+    assert_eq!(host, Cidr::from_text("10.0.0.1/32").unwrap());
+    assert!(host.is_match(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
+    assert!(!host.is_match(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))));
+
+    let host = Cidr::from_text("2001:db8::1").expect("to parse bare ipv6");
+    assert_eq!(host.prefix(), 128);
+    assert_eq!(host, Cidr::from_text("2001:db8::1/128").unwrap());
+
+    let network = Cidr::from_text("10.0.0.0/8").expect("to parse network");
+    assert_eq!(network.prefix(), 8);
+    assert!(Cidr::from_text("10.0.0.1/33").is_err());
+}
+
+#[test]
+fn should_match_same_prefix() {
+    use http_ip::filter::{Filter, SamePrefix};
+
+    let reference = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    let candidate = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 37));
+
+    assert!(SamePrefix { reference, prefix: 24 }.is_match(candidate));
+    assert!(!SamePrefix { reference, prefix: 32 }.is_match(candidate));
+    assert!(SamePrefix { reference, prefix: 32 }.is_match(reference));
+    assert!(SamePrefix { reference, prefix: 0 }.is_match(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))));
+    assert!(!SamePrefix { reference, prefix: 24 }.is_match(IpAddr::V4(Ipv4Addr::new(10, 0, 1, 37))));
+    assert!(!SamePrefix { reference, prefix: 0 }.is_match("::1".parse().unwrap()));
+
+    let reference: IpAddr = "2001:db8::1".parse().unwrap();
+    assert!(SamePrefix::new(reference, 64).is_match("2001:db8::ffff".parse().unwrap()));
+    assert!(!SamePrefix::new(reference, 64).is_match("2001:db9::1".parse().unwrap()));
+    assert!(!SamePrefix::new(reference, 200).is_match("2001:db8::2".parse().unwrap()));
+}
+
+#[test]
+fn should_parse_space_separated_x_forwarded_for_in_lenient_mode() {
+    use http_ip::forwarded::{parse_x_forwarded_for_lenient, parse_x_forwarded_for_lenient_rev};
+
+    let first = ForwardedNode::Ip(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 1)));
+    let second = ForwardedNode::Ip(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 2)));
+    let third = ForwardedNode::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+
+    let mut strict = parse_x_forwarded_for("203.0.113.1 198.51.100.2");
+    assert_eq!(strict.next(), Some(ForwardedNode::Name("203.0.113.1 198.51.100.2")));
+    assert_eq!(strict.next(), None);
+
+    let nodes = parse_x_forwarded_for_lenient("203.0.113.1 198.51.100.2");
+    assert_eq!(nodes.len(), 2);
+    assert!(nodes.eq([first, second]));
+
+    let nodes = parse_x_forwarded_for_lenient(" 203.0.113.1,\t198.51.100.2 , 10.0.0.1 ");
+    assert_eq!(nodes.len(), 3);
+    assert!(nodes.eq([first, second, third]));
+
+    let nodes = parse_x_forwarded_for_lenient_rev("203.0.113.1 198.51.100.2,10.0.0.1");
+    assert!(nodes.eq([third, second, first]));
+
+    assert_eq!(parse_x_forwarded_for_lenient(" , ").next(), None);
+}
+
+#[test]
+fn should_construct_forwarded_node() {
+    use core::fmt::Write;
+
+    fn display(node: ForwardedNode<'_>) -> FixedBuf {
+        let mut out = FixedBuf::new();
+        write!(out, "{node}").expect("to write");
+        out
+    }
+
+    let ip: IpAddr = "2001:db8::1".parse().unwrap();
+    let node = ForwardedNode::from_ip(ip);
+    assert_eq!(node, ForwardedNode::Ip(ip));
+    assert_eq!(node, ForwardedNode::from(ip));
+    assert_eq!(display(node).as_str(), "2001:db8::1");
+    assert_eq!(ForwardedNode::parse_x_node(display(node).as_str()), node);
+
+    let node = ForwardedNode::from_name("_hidden");
+    assert_eq!(node, ForwardedNode::Name("_hidden"));
+    assert_eq!(display(node).as_str(), "_hidden");
+    assert_eq!(ForwardedNode::parse_node(display(node).as_str()), node);
+
+    let node = ForwardedNode::unknown();
+    assert_eq!(node, ForwardedNode::Unknown);
+    assert_eq!(display(node).as_str(), "-");
+
+    let node: ForwardedNode<'_> = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)).into();
+    assert_eq!(display(node).as_str(), "192.0.2.1");
+}
+
+#[test]
+fn should_check_global_ip() {
+    use http_ip::filter::is_global;
+
+    for ip in ["0.0.0.0", "10.1.1.1", "172.16.0.1", "192.168.1.1", "127.0.0.1", "169.254.1.1", "100.64.0.1", "192.0.0.8", "198.18.0.1", "240.0.0.1", "255.255.255.255", "224.0.0.1", "::", "::1", "fd00::1", "fe80::1", "ff02::1", "100::1", "::ffff:10.0.0.1"] {
+        assert!(!is_global(ip.parse().unwrap()), "{ip} should not be global");
+    }
+
+    for ip in ["1.1.1.1", "8.8.8.8", "203.0.113.5", "100.128.0.1", "2606:4700::1111", "2001:db8::1", "::ffff:8.8.8.8"] {
+        assert!(is_global(ip.parse().unwrap()), "{ip} should be global");
+    }
+}
+
+#[test]
+fn should_check_global_ip_in_special_purpose_blocks() {
+    use http_ip::filter::is_global;
+
+    for ip in ["192.0.0.1", "64:ff9b:1::1", "2001::1", "2001:2::1", "2001:1::3", "2001:1ff::1", "2002:c000:204::1", "5f00::1", "ff0e::1"] {
+        assert!(!is_global(ip.parse().unwrap()), "{ip} should not be global");
+    }
+
+    for ip in ["192.0.0.9", "192.0.0.10", "64:ff9b::808:808", "2001:1::1", "2001:1::2", "2001:3::1", "2001:4:112::1", "2001:20::1", "2001:3f::1", "2001:200::1", "3fff::1", "192.0.2.1", "198.51.100.1"] {
+        assert!(is_global(ip.parse().unwrap()), "{ip} should be global");
+    }
+}
+
+#[test]
+fn should_trim_whitespace_around_forwarded_entries() {
+    let first = ForwardedNode::Ip(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)));
+    let second = ForwardedNode::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+
+    assert!(parse_forwarded_for("For=127.0.0.1, For=10.0.0.1").eq([first, second]));
+    assert!(parse_forwarded_for_rev("For=127.0.0.1 ,\tFor=10.0.0.1").eq([second, first]));
+    assert!(parse_forwarded_for("for=127.0.0.1; proto=https, for=10.0.0.1 ").eq([first, second]));
+
+    let mut entries = parse_forwarded("for=127.0.0.1, by=10.0.0.1; proto=https");
+    let mut entry = entries.next().expect("first entry");
+    assert_eq!(entry.next(), Some(ForwardedValue::For(first)));
+    assert_eq!(entry.next(), None);
+    let mut entry = entries.next().expect("second entry");
+    assert_eq!(entry.next(), Some(ForwardedValue::By(second)));
+    assert_eq!(entry.next(), Some(ForwardedValue::Protocol("https")));
+    assert_eq!(entry.next(), None);
+    assert!(entries.next().is_none());
+}
+
+#[test]
+fn should_evaluate_cheaper_filter_first_in_or() {
+    use core::cell::Cell;
+    use http_ip::filter::{self, Cidr, Filter};
+
+    struct Expensive<'a>(&'a Cell<usize>);
+
+    impl Filter for Expensive<'_> {
+        fn is_match(&self, _: IpAddr) -> bool {
+            self.0.set(self.0.get() + 1);
+            false
+        }
+
+        fn cost_hint(&self) -> u32 {
+            100
+        }
+    }
+
+    let ip = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    let calls = Cell::new(0);
+    let filter = filter::or(Expensive(&calls), ip);
+    assert_eq!(filter.cost_hint(), 101);
+    assert!(filter.is_match(ip));
+    assert_eq!(calls.get(), 0);
+    assert!(!filter.is_match(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2))));
+    assert_eq!(calls.get(), 1);
+
+    let cidr = Cidr::from_text("10.0.0.0/8").unwrap();
+    assert!(ip.cost_hint() < cidr.cost_hint());
+    assert!(cidr.cost_hint() < Expensive(&calls).cost_hint());
+    assert_eq!(().cost_hint(), 0);
+    assert_eq!(filter::collection_or([cidr, cidr]).cost_hint(), 4);
+    assert_eq!(filter::collection_or([&ip as &dyn Filter, &cidr, &cidr]).cost_hint(), 5);
+}
+
+#[test]
+fn should_parse_unbracketed_ipv6_forwarded_node() {
+    let expected_ip: IpAddr = "2001:db8::1".parse().unwrap();
+
+    assert_eq!(ForwardedNode::parse_node("2001:db8::1"), ForwardedNode::Ip(expected_ip));
+    assert_eq!(ForwardedNode::parse_node("\"2001:db8::1\""), ForwardedNode::Ip(expected_ip));
+    assert_eq!(ForwardedNode::parse_node("\"[2001:db8::1]\""), ForwardedNode::Ip(expected_ip));
+    assert_eq!(ForwardedNode::parse_node("\"192.0.2.1:4711\""), ForwardedNode::Ip(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))));
+
+    let nodes = parse_forwarded_for("For=10.0.0.1, For=2001:db8::1, For=\"[2001:db8::2]\"");
+    assert!(nodes.eq([
+        ForwardedNode::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))),
+        ForwardedNode::Ip(expected_ip),
+        ForwardedNode::Ip("2001:db8::2".parse().unwrap()),
+    ]));
+}
+
+#[test]
+fn should_skip_empty_for_value() {
+    use http_ip::forwarded::ForwardedEntryIter;
+
+    let mut entry = ForwardedEntryIter::parse_entry("for=;proto=https");
+    assert_eq!(entry.next(), Some(ForwardedValue::Protocol("https")));
+    assert_eq!(entry.next(), None);
+
+    let mut entry = ForwardedEntryIter::parse_entry("for=\"\";by=");
+    assert_eq!(entry.next(), None);
+
+    let mut entry = ForwardedEntryIter::parse_entry("for=127.0.0.1;");
+    assert_eq!(entry.next(), Some(ForwardedValue::For(ForwardedNode::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)))));
+    assert_eq!(entry.next(), None);
+
+    assert!(parse_forwarded_for("for=;proto=https,for=127.0.0.1;").eq([ForwardedNode::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST))]));
+    assert!(parse_forwarded_for_rev("for=127.0.0.1,for=").eq([ForwardedNode::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST))]));
+}
+
+#[test]
+fn should_map_ip_before_matching() {
+    use http_ip::filter::{Cidr, Filter, FilterExt};
+
+    let mapped: IpAddr = "::ffff:10.0.0.1".parse().unwrap();
+    let cidr = Cidr::from_text("10.0.0.0/8").unwrap();
+    assert!(!cidr.is_match(mapped));
+
+    let filter = cidr.map_ip(|ip| ip.to_canonical());
+    assert!(filter.is_match(mapped));
+    assert!(filter.is_match(IpAddr::V4(Ipv4Addr::new(10, 1, 1, 1))));
+    assert!(!filter.is_match("::ffff:192.168.0.1".parse().unwrap()));
+
+    let nodes = parse_x_forwarded_for_rev("203.0.113.195, ::ffff:10.0.0.2, 10.0.0.1");
+    assert_eq!(find_next_ip_after_filter(nodes, &filter), Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 195))));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_box_filter_via_extension() {
+    use http_ip::filter::{Cidr, Filter, FilterExt, FilterList};
+
+    let filter = Cidr::from_text("10.0.0.0/8").unwrap().map_ip(|ip| ip.to_canonical()).boxed();
+    assert!(filter.is_match("::ffff:10.0.0.1".parse().unwrap()));
+
+    let nodes = parse_x_forwarded_for_rev("203.0.113.195, 10.0.0.1");
+    assert_eq!(find_next_ip_after_filter(nodes, &*filter), Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 195))));
+
+    let mut list = FilterList::new();
+    list.push_boxed(IpAddr::V4(Ipv4Addr::LOCALHOST).boxed());
+    assert!(list.is_match(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+}
+
+#[test]
+fn should_reject_too_broad_cidr() {
+    use http_ip::filter::Cidr;
+
+    let cidr = Cidr::from_text_bounded("10.0.0.0/16", 8).expect("to accept /16");
+    assert_eq!(cidr, Cidr::from_text("10.0.0.0/16").unwrap());
+    assert!(Cidr::from_text_bounded("10.0.0.0/8", 8).is_ok());
+    assert!(Cidr::from_text_bounded("10.0.0.1", 8).is_ok());
+
+    let error = Cidr::from_text_bounded("10.0.0.0/4", 8).expect_err("to reject /4");
+    assert_eq!(error.to_string(), "CIDR prefix is broader than allowed");
+    assert!(Cidr::from_text_bounded("2001:db8::/7", 8).is_err());
+    assert!(Cidr::from_text_bounded("10.0.0.0/33", 8).is_err());
+}
+
+#[test]
+fn should_parse_single_x_forwarded_for_same_as_iterator() {
+    use http_ip::forwarded::parse_x_forwarded_for_single;
+
+    for value in ["203.0.113.195", " 203.0.113.195 ", "2001:db8::1", "[2001:db8::1]:443", "203.0.113.195:443", "_hidden", "unknown", ""] {
+        let mut nodes = parse_x_forwarded_for(value);
+        assert_eq!(parse_x_forwarded_for_single(value), nodes.next(), "{value}");
+        assert_eq!(nodes.next(), None);
+    }
+
+    assert_eq!(parse_x_forwarded_for_single("203.0.113.195, 10.0.0.1"), None);
+    assert_eq!(parse_x_forwarded_for_single(","), None);
+}
+
+#[test]
+fn should_recover_ip_from_name_node() {
+    let expected = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 5));
+
+    assert_eq!(ForwardedNode::Name("ip=203.0.113.5").recover_ip(), Some(expected));
+    assert_eq!(ForwardedNode::Name("203.0.113.5/proxy").recover_ip(), Some(expected));
+    assert_eq!(ForwardedNode::Name("client:2001:db8::1").recover_ip(), Some("2001:db8::1".parse().unwrap()));
+    assert_eq!(ForwardedNode::Name("proxy").recover_ip(), None);
+    assert_eq!(ForwardedNode::Name("cafe").recover_ip(), None);
+    assert_eq!(ForwardedNode::Name("").recover_ip(), None);
+    assert_eq!(ForwardedNode::Unknown.recover_ip(), None);
+    assert_eq!(ForwardedNode::Ip(expected).recover_ip(), Some(expected));
+}
+
+#[test]
+fn should_canonicalize_ip() {
+    use http_ip::filter::{canonicalize_ip, Cidr, Filter, FilterExt};
+
+    let expected = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+    assert_eq!(canonicalize_ip("::ffff:10.0.0.1".parse().unwrap()), expected);
+    assert_eq!(canonicalize_ip("::10.0.0.1".parse().unwrap()), expected);
+    assert_eq!(canonicalize_ip(expected), expected);
+
+    for ip in ["2001:db8::1", "::", "::1", "::ffff:0:10.0.0.1"] {
+        let ip: IpAddr = ip.parse().unwrap();
+        assert_eq!(canonicalize_ip(ip), ip);
+    }
+
+    let filter = Cidr::from_text("10.0.0.0/8").unwrap().map_ip(canonicalize_ip);
+    assert!(filter.is_match("::ffff:10.0.0.1".parse().unwrap()));
+}
+
+#[test]
+fn should_resolve_from_multiple_forwarded_values() {
+    use http_ip::filter::Cidr;
+    use http_ip::forwarded::resolve_from_values;
+
+    let filter = Cidr::from_text("10.0.0.0/24").unwrap();
+    let values = ["for=203.0.113.195;proto=https", "for=198.51.100.178,for=10.0.0.2", "for=10.0.0.1"];
+
+    assert_eq!(resolve_from_values(values.iter().copied(), &filter), Some(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 178))));
+    assert_eq!(resolve_from_values(values[..1].iter().copied(), &filter), Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 195))));
+    assert_eq!(resolve_from_values(["for=_hidden", "for=10.0.0.1"].into_iter(), &filter), None);
+    assert_eq!(resolve_from_values([].into_iter(), &filter), None);
+}
+
+#[test]
+fn should_capture_port_of_obfuscated_node() {
+    assert_eq!(ForwardedNode::parse_node("_hidden:8080"), ForwardedNode::Name("_hidden"));
+    assert_eq!(ForwardedNode::parse_node("_hidden"), ForwardedNode::Name("_hidden"));
+
+    assert_eq!(ForwardedNode::parse_node_with_port("\"_hidden:8080\""), (ForwardedNode::Name("_hidden"), Some("8080")));
+    assert_eq!(ForwardedNode::parse_node_with_port("_hidden"), (ForwardedNode::Name("_hidden"), None));
+    assert_eq!(ForwardedNode::parse_node_with_port("_hidden:_port"), (ForwardedNode::Name("_hidden"), Some("_port")));
+
+    let ip: IpAddr = "2001:db8::1".parse().unwrap();
+    assert_eq!(ForwardedNode::parse_node_with_port("\"[2001:db8::1]:4711\""), (ForwardedNode::Ip(ip), Some("4711")));
+    assert_eq!(ForwardedNode::parse_node_with_port("[2001:db8::1]"), (ForwardedNode::Ip(ip), None));
+    assert_eq!(ForwardedNode::parse_node_with_port("2001:db8::1"), (ForwardedNode::Ip(ip), None));
+    assert_eq!(ForwardedNode::parse_node_with_port("\"192.0.2.1:80\""), (ForwardedNode::Ip(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))), Some("80")));
+    assert_eq!(ForwardedNode::parse_node_with_port("unknown"), (ForwardedNode::Unknown, None));
+
+    assert_eq!(ForwardedNode::parse_node_with_port("\"1.2.3.4:99999x\""), (ForwardedNode::Name("1.2.3.4:99999x"), None));
+    assert_eq!(ForwardedNode::parse_node_with_port("1.2.3.4:99999"), (ForwardedNode::Name("1.2.3.4:99999"), None));
+    assert_eq!(ForwardedNode::parse_node_with_port("_hidden:_"), (ForwardedNode::Name("_hidden:_"), None));
+    assert_eq!(parse_forwarded_for("for=\"1.2.3.4:99999x\"").collect::<Vec<_>>(), [ForwardedNode::Name("1.2.3.4:99999x")]);
+}
+
+#[test]
+fn should_debug_chain() {
+    use core::fmt::Write;
+    use http_ip::forwarded::{debug_chain, debug_x_chain};
+
+    const IPS: &str = "203.0.113.195,2001:db8:85a3:8d3:1319:8a2e:370:7348,198.51.100.178";
+
+    let mut out = FixedBuf::new();
+    write!(out, "{:?}", debug_x_chain(IPS)).expect("to write");
+    assert_eq!(out.as_str(), "[Ip(203.0.113.195), Ip(2001:db8:85a3:8d3:1319:8a2e:370:7348), Ip(198.51.100.178)]");
+
+    let mut out = FixedBuf::new();
+    write!(out, "{:?}", debug_chain("for=203.0.113.1;proto=https,for=unknown, for=_hidden")).expect("to write");
+    assert_eq!(out.as_str(), "[Ip(203.0.113.1), Unknown, Name(\"_hidden\")]");
+
+    let mut out = FixedBuf::new();
+    write!(out, "{:?}", debug_chain("")).expect("to write");
+    assert_eq!(out.as_str(), "[]");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_build_owned_forwarded_chain() {
+    use http_ip::filter::Cidr;
+    use http_ip::forwarded::{OwnedForwardedChain, OwnedForwardedNode};
+
+    fn assert_static<T: 'static>(value: T) -> T {
+        value
+    }
+
+    let header = String::from("for=203.0.113.195;proto=https,for=_hidden,for=10.0.0.1");
+    let chain = assert_static(OwnedForwardedChain::parse_forwarded(&header));
+    drop(header);
+
+    assert_eq!(chain.len(), 3);
+    assert_eq!(chain.nodes(), [
+        OwnedForwardedNode::Ip("203.0.113.195".parse().unwrap()),
+        OwnedForwardedNode::Name("_hidden".to_owned()),
+        OwnedForwardedNode::Ip("10.0.0.1".parse().unwrap()),
+    ]);
+    assert!(chain.iter().eq([
+        ForwardedNode::Ip("203.0.113.195".parse().unwrap()),
+        ForwardedNode::Name("_hidden"),
+        ForwardedNode::Ip("10.0.0.1".parse().unwrap()),
+    ]));
+    assert_eq!(chain.nodes()[1].ip(), None);
+
+    let filter = Cidr::from_text("10.0.0.0/24").unwrap();
+    assert_eq!(find_next_ip_after_filter(chain.iter().rev(), &filter), None);
+
+    let header = String::from("203.0.113.195, 10.0.0.1");
+    let chain = OwnedForwardedChain::parse_x_forwarded_for(&header);
+    drop(header);
+    assert_eq!(find_next_ip_after_filter(chain.iter().rev(), &filter), Some("203.0.113.195".parse().unwrap()));
+    assert!(OwnedForwardedChain::default().is_empty());
+}
+
+#[test]
+fn should_match_cidr_set() {
+    use http_ip::filter::{Cidr, CidrSet, Filter};
+
+    const fn cidr(text: &str) -> Cidr {
+        match Cidr::from_text(text) {
+            Ok(cidr) => cidr,
+            Err(_) => panic!("invalid cidr"),
+        }
+    }
+
+    const FLEET: &[Cidr] = &[cidr("10.0.0.0/8"), cidr("172.16.0.0/12"), cidr("2001:db8::/32")];
+    const SET: CidrSet<&[Cidr]> = CidrSet::new(FLEET);
+    const { assert!(SET.contains_const(IpAddr::V4(Ipv4Addr::new(172, 16, 5, 4)))) };
+
+    assert!(SET.is_match(IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3))));
+    assert!(SET.is_match("2001:db8::1".parse().unwrap()));
+    assert!(!SET.is_match(IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1))));
+    assert!(!SET.contains_const("2001:db9::1".parse().unwrap()));
+    assert!(!CidrSet::new([] as [Cidr; 0]).is_match(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+
+    let nodes = parse_x_forwarded_for_rev("203.0.113.195, 172.16.0.1, 10.0.0.1");
+    assert_eq!(find_next_ip_after_filter(nodes, &CidrSet::new(FLEET)), Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 195))));
+}
+
+#[test]
+fn should_match_and_filter() {
+    use http_ip::filter::{self, Cidr, Filter};
+
+    const PRIVATE: Cidr = match Cidr::from_text("10.0.0.0/8") {
+        Ok(cidr) => cidr,
+        Err(_) => panic!("invalid cidr"),
+    };
+    const TRUSTED: Cidr = match Cidr::from_text("10.1.0.0/16") {
+        Ok(cidr) => cidr,
+        Err(_) => panic!("invalid cidr"),
+    };
+    let filter = filter::and(PRIVATE, TRUSTED);
+
+    assert!(filter.is_match(IpAddr::V4(Ipv4Addr::new(10, 1, 2, 3))));
+    assert!(!filter.is_match(IpAddr::V4(Ipv4Addr::new(10, 2, 2, 3))));
+    assert!(!filter.is_match(IpAddr::V4(Ipv4Addr::new(192, 168, 2, 3))));
+
+    let filter = PRIVATE.and(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+    assert!(filter.is_match(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
+    assert!(!filter.is_match(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))));
+    assert_eq!(filter.cost_hint(), 3);
+
+    let filter = PRIVATE.and(TRUSTED).or(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    assert!(filter.is_match(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    assert!(filter.is_match(IpAddr::V4(Ipv4Addr::new(10, 1, 0, 1))));
+}
+
+#[test]
+fn should_match_not_filter() {
+    use http_ip::filter::{self, Cidr, Filter};
+
+    const EGRESS: Cidr = match Cidr::from_text("203.0.113.0/24") {
+        Ok(cidr) => cidr,
+        Err(_) => panic!("invalid cidr"),
+    };
+    const NOT_EGRESS: filter::Not<Cidr> = filter::not(EGRESS);
+
+    assert!(NOT_EGRESS.is_match(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1))));
+    assert!(!NOT_EGRESS.is_match(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 5))));
+
+    //Skip everything until egress range is reached
+    let nodes = parse_x_forwarded_for_rev("198.51.100.1, 203.0.113.5, 10.0.0.2, 10.0.0.1");
+    assert_eq!(find_next_ip_after_filter(nodes, &EGRESS.not()), Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 5))));
+
+    assert!(().not().is_match(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+    assert!(!EGRESS.not().not().is_match(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+}
+
+#[test]
+fn should_match_preset_filters() {
+    use http_ip::filter::{Filter, PRIVATE_NETWORKS, LOOPBACK, LINK_LOCAL, NON_PUBLIC};
+
+    for ip in ["10.1.1.1", "172.31.255.255", "192.168.0.1", "100.64.0.1", "fd12::1"] {
+        let ip: IpAddr = ip.parse().unwrap();
+        assert!(PRIVATE_NETWORKS.is_match(ip), "{ip}");
+        assert!(!LOOPBACK.is_match(ip), "{ip}");
+        assert!(NON_PUBLIC.is_match(ip), "{ip}");
+    }
+
+    for ip in ["127.0.0.1", "::1"] {
+        let ip: IpAddr = ip.parse().unwrap();
+        assert!(LOOPBACK.is_match(ip), "{ip}");
+        assert!(!PRIVATE_NETWORKS.is_match(ip), "{ip}");
+        assert!(NON_PUBLIC.is_match(ip), "{ip}");
+    }
+
+    for ip in ["169.254.169.254", "fe80::1"] {
+        let ip: IpAddr = ip.parse().unwrap();
+        assert!(LINK_LOCAL.is_match(ip), "{ip}");
+        assert!(NON_PUBLIC.is_match(ip), "{ip}");
+    }
+
+    for ip in ["203.0.113.5", "172.32.0.1", "100.128.0.1", "2001:db8::1"] {
+        assert!(!NON_PUBLIC.is_match(ip.parse().unwrap()), "{ip}");
+    }
+
+    let nodes = parse_x_forwarded_for_rev("203.0.113.195, 100.64.0.1, 10.0.0.1, 127.0.0.1");
+    assert_eq!(find_next_ip_after_filter(nodes, &NON_PUBLIC), Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 195))));
+}
+
+#[test]
+fn should_match_slices_and_arrays_as_or_filter() {
+    use http_ip::filter::{Cidr, Filter};
+
+    const BLOCKS: [Cidr; 2] = [
+        match Cidr::from_text("10.0.0.0/8") { Ok(cidr) => cidr, Err(_) => panic!() },
+        match Cidr::from_text("192.168.0.0/16") { Ok(cidr) => cidr, Err(_) => panic!() },
+    ];
+    const PROXIES: [IpAddr; 2] = [IpAddr::V4(Ipv4Addr::new(203, 0, 113, 1)), IpAddr::V4(Ipv4Addr::new(203, 0, 113, 2))];
+
+    assert!(BLOCKS.is_match("10.1.1.1".parse().unwrap()));
+    assert!(BLOCKS.as_slice().is_match("192.168.1.1".parse().unwrap()));
+    assert!(!BLOCKS.is_match("172.16.1.1".parse().unwrap()));
+    assert!(PROXIES.is_match(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 2))));
+    assert!(!PROXIES.as_slice().is_match(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 3))));
+
+    let empty: &[Cidr] = &[];
+    assert!(!empty.is_match("10.1.1.1".parse().unwrap()));
+    assert_eq!(empty.cost_hint(), 0);
+
+    let nodes = parse_x_forwarded_for_rev("198.51.100.7, 10.0.0.1, 192.168.0.1");
+    assert_eq!(find_next_ip_after_filter(nodes, &BLOCKS), Some(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 7))));
+    let nodes = parse_x_forwarded_for_rev("198.51.100.7, 203.0.113.1, 203.0.113.2");
+    assert_eq!(find_next_ip_after_filter(nodes, PROXIES.as_slice()), Some(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 7))));
+}
+
+#[test]
+fn should_match_fn_filter() {
+    use http_ip::filter::{self, Filter};
+
+    let blocked = [IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))];
+    let filter = filter::from_fn(|ip| blocked.contains(&ip));
+    assert!(filter.is_match(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2))));
+    assert!(!filter.is_match(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 3))));
+
+    let filter = filter.or(filter::FnFilter(|ip: IpAddr| ip.is_loopback()));
+    let nodes = parse_x_forwarded_for_rev("198.51.100.7, 10.0.0.1, 127.0.0.1, 10.0.0.2");
+    assert_eq!(find_next_ip_after_filter(nodes, &filter), Some(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 7))));
+}
+
+#[test]
+fn should_match_optional_filter() {
+    use http_ip::filter::{Cidr, Filter};
+
+    const FORWARDED: &str = "For=203.0.113.195,For=10.0.0.1";
+
+    let filter: Option<Cidr> = None;
+    assert!(!filter.is_match("10.0.0.1".parse().unwrap()));
+    assert_eq!(filter.cost_hint(), 0);
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &filter).expect("Find ip");
+    assert_eq!(ip, "10.0.0.1".parse::<IpAddr>().unwrap());
+
+    let filter = Cidr::from_text("10.0.0.0/24").ok();
+    assert!(filter.is_match("10.0.0.1".parse().unwrap()));
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &filter).expect("Find ip");
+    assert_eq!(ip, "203.0.113.195".parse::<IpAddr>().unwrap());
+}
+
+#[test]
+fn should_match_tuple_as_or_filter() {
+    use http_ip::filter::{Cidr, Filter};
+
+    const CIDR_A: Cidr = match Cidr::from_text("10.0.0.0/8") { Ok(cidr) => cidr, Err(_) => panic!() };
+    const CIDR_B: Cidr = match Cidr::from_text("192.168.0.0/16") { Ok(cidr) => cidr, Err(_) => panic!() };
+    const LB_IP: IpAddr = IpAddr::V4(Ipv4Addr::new(203, 0, 113, 1));
+    const FILTER: (Cidr, Cidr, IpAddr) = (CIDR_A, CIDR_B, LB_IP);
+
+    assert!(FILTER.is_match("10.1.1.1".parse().unwrap()));
+    assert!(FILTER.is_match("192.168.1.1".parse().unwrap()));
+    assert!(FILTER.is_match(LB_IP));
+    assert!(!FILTER.is_match("203.0.113.2".parse().unwrap()));
+    assert_eq!(FILTER.cost_hint(), 5);
+    assert!((LB_IP,).is_match(LB_IP));
+
+    let nodes = parse_x_forwarded_for_rev("198.51.100.7, 10.0.0.1, 192.168.0.1, 203.0.113.1");
+    assert_eq!(find_next_ip_after_filter(nodes, &FILTER), Some(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 7))));
+}
+
+#[test]
+fn should_match_none_or_all() {
+    use http_ip::filter::{Filter, MatchAll, MatchNone};
+
+    const FORWARDED: &str = "For=203.0.113.195,For=10.0.0.1";
+
+    assert!(!MatchNone.is_match("10.0.0.1".parse().unwrap()));
+    assert!(MatchAll.is_match("10.0.0.1".parse().unwrap()));
+    assert!(MatchAll.is_match("::1".parse().unwrap()));
+
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &MatchNone).expect("Find ip");
+    assert_eq!(ip, "10.0.0.1".parse::<IpAddr>().unwrap());
+    assert!(find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &MatchAll).is_none());
+}
+
+#[test]
+fn should_match_ip_range() {
+    use http_ip::filter::{Filter, IpRange};
+
+    let range = IpRange::new("10.1.2.17".parse().unwrap(), "10.1.2.63".parse().unwrap()).expect("valid range");
+    assert!(range.is_match("10.1.2.17".parse().unwrap()));
+    assert!(range.is_match("10.1.2.40".parse().unwrap()));
+    assert!(range.is_match("10.1.2.63".parse().unwrap()));
+    assert!(!range.is_match("10.1.2.16".parse().unwrap()));
+    assert!(!range.is_match("10.1.2.64".parse().unwrap()));
+    assert!(!range.is_match("::ffff:10.1.2.40".parse().unwrap()));
+
+    let range = IpRange::new("2001:db8::10".parse().unwrap(), "2001:db8::1:0".parse().unwrap()).expect("valid range");
+    assert!(range.is_match("2001:db8::ffff".parse().unwrap()));
+    assert!(range.is_match("2001:db8::1:0".parse().unwrap()));
+    assert!(!range.is_match("2001:db8::f".parse().unwrap()));
+    assert!(!range.is_match("10.1.2.40".parse().unwrap()));
+
+    let single = IpRange::new("10.0.0.1".parse().unwrap(), "10.0.0.1".parse().unwrap()).expect("valid range");
+    assert!(single.is_match("10.0.0.1".parse().unwrap()));
+
+    assert!(IpRange::new("10.0.0.2".parse().unwrap(), "10.0.0.1".parse().unwrap()).is_none());
+    assert!(IpRange::new("10.0.0.1".parse().unwrap(), "::1".parse().unwrap()).is_none());
+}
+
+#[test]
+fn should_convert_ip_range_to_cidrs() {
+    use http_ip::filter::{Cidr, Filter, IpRange};
+
+    let range = IpRange::new("10.1.2.17".parse().unwrap(), "10.1.2.63".parse().unwrap()).expect("valid range");
+    let expected = [
+        Cidr::from_text("10.1.2.17/32").unwrap(),
+        Cidr::from_text("10.1.2.18/31").unwrap(),
+        Cidr::from_text("10.1.2.20/30").unwrap(),
+        Cidr::from_text("10.1.2.24/29").unwrap(),
+        Cidr::from_text("10.1.2.32/27").unwrap(),
+    ];
+    assert!(range.to_cidrs().eq(expected));
+    for last in 0..=255u8 {
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 1, 2, last));
+        assert_eq!(range.to_cidrs().any(|cidr| cidr.is_match(ip)), range.is_match(ip), "{ip}");
+    }
+
+    let range = IpRange::new("10.0.0.0".parse().unwrap(), "10.255.255.255".parse().unwrap()).expect("valid range");
+    assert!(range.to_cidrs().eq([Cidr::from_text("10.0.0.0/8").unwrap()]));
+
+    let range = IpRange::new("0.0.0.0".parse().unwrap(), "255.255.255.255".parse().unwrap()).expect("valid range");
+    assert!(range.to_cidrs().eq([Cidr::from_text("0.0.0.0/0").unwrap()]));
+
+    let range = IpRange::new("255.255.255.254".parse().unwrap(), "255.255.255.255".parse().unwrap()).expect("valid range");
+    let mut cidrs = range.to_cidrs();
+    assert_eq!(cidrs.next().map(|cidr| cidr.prefix()), Some(31));
+    assert!(cidrs.next().is_none());
+    assert!(cidrs.next().is_none());
+
+    let range = IpRange::new("::".parse().unwrap(), "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff".parse().unwrap()).expect("valid range");
+    assert!(range.to_cidrs().eq([Cidr::from_text("::/0").unwrap()]));
+
+    let range = IpRange::new("2001:db8::1".parse().unwrap(), "2001:db8::4".parse().unwrap()).expect("valid range");
+    let expected = [
+        Cidr::from_text("2001:db8::1/128").unwrap(),
+        Cidr::from_text("2001:db8::2/127").unwrap(),
+        Cidr::from_text("2001:db8::4/128").unwrap(),
+    ];
+    assert!(range.to_cidrs().eq(expected));
+}
+
+#[test]
+fn should_parse_cidrs_macro() {
+    use http_ip::filter::{Cidr, CidrSet, Filter};
+
+    const TRUSTED: [Cidr; 3] = http_ip::cidrs!["10.0.0.0/8", "192.168.0.0/16", "2001:db8::1",];
+    const TRUSTED_SET: CidrSet<&[Cidr]> = CidrSet::new(&TRUSTED);
+    assert_eq!(TRUSTED[0], Cidr::from_text("10.0.0.0/8").unwrap());
+    assert_eq!(TRUSTED[2].prefix(), 128);
+    assert!(TRUSTED_SET.is_match("192.168.1.1".parse().unwrap()));
+    assert!(!TRUSTED_SET.is_match("172.16.1.1".parse().unwrap()));
+
+    let runtime = http_ip::cidrs!["127.0.0.0/8"];
+    assert!(runtime.is_match("127.0.0.1".parse().unwrap()));
+    let empty: [Cidr; 0] = http_ip::cidrs![];
+    assert!(!empty.is_match("127.0.0.1".parse().unwrap()));
+}
+
+#[test]
+fn should_parse_cidr_text_list() {
+    use http_ip::filter::{Cidr, CidrSet, Filter};
+
+    const TRUSTED: [Cidr; 3] = match Cidr::from_text_list("10.0.0.0/8, 172.16.0.0/12 ,2001:db8::/32,") {
+        Ok(list) => list,
+        Err(_) => panic!("invalid list"),
+    };
+    const TRUSTED_SET: CidrSet<&[Cidr]> = CidrSet::new(&TRUSTED);
+
+    assert_eq!(TRUSTED, http_ip::cidrs!["10.0.0.0/8", "172.16.0.0/12", "2001:db8::/32"]);
+    assert!(TRUSTED_SET.is_match("172.16.1.1".parse().unwrap()));
+
+    let empty = Cidr::from_text_list::<0>(" , ").expect("empty list");
+    assert!(empty.is_empty());
+
+    let error = Cidr::from_text_list::<2>("10.0.0.0/8").unwrap_err();
+    assert_eq!(error.to_string(), "Number of CIDR entries doesn't match expected");
+    let error = Cidr::from_text_list::<1>("10.0.0.0/8, 10.0.0.1").unwrap_err();
+    assert_eq!(error.to_string(), "Number of CIDR entries doesn't match expected");
+    assert!(Cidr::from_text_list::<2>("10.0.0.0/8, 10.0.0.0/33").is_err());
+
+    let list = Cidr::from_text_list::<3>("10.0.0.0/8 192.168.0.0/16\n\t2001:db8::/32\n").expect("whitespace separated list");
+    assert_eq!(list, http_ip::cidrs!["10.0.0.0/8", "192.168.0.0/16", "2001:db8::/32"]);
+    let error = Cidr::from_text_list::<1>("10.0.0.0/8 192.168.0.0/16").unwrap_err();
+    assert_eq!(error.to_string(), "Number of CIDR entries doesn't match expected");
+}
+
+#[test]
+fn should_parse_cidr_from_str() {
+    use http_ip::filter::{Cidr, CidrParseError};
+
+    fn load(text: &str) -> Result<Cidr, Box<dyn std::error::Error>> {
+        let cidr: Cidr = text.trim().parse()?;
+        Ok(cidr)
+    }
+
+    assert_eq!(load(" 10.0.0.0/8 ").unwrap(), Cidr::from_text("10.0.0.0/8").unwrap());
+    assert_eq!(Cidr::try_from("2001:db8::/32").unwrap(), Cidr::from_text("2001:db8::/32").unwrap());
+
+    let error = load("10.0.0.0/33").unwrap_err();
+    assert_eq!(error.to_string(), Cidr::from_text("10.0.0.0/33").unwrap_err().to_string());
+    let error: CidrParseError<'static> = "10.0.0.256/8".parse::<Cidr>().unwrap_err();
+    assert_eq!(error, Cidr::try_from("1.1.1.1111").unwrap_err());
+}
+
+#[test]
+fn should_match_cloudflare_ranges() {
+    use http_ip::filter::Filter;
+    use http_ip::filter::cloud::{CLOUDFLARE, parse_cloudflare_ips};
+
+    for ip in ["173.245.48.1", "104.16.0.1", "172.71.255.255", "2606:4700::1", "2a06:98c7::1"] {
+        assert!(CLOUDFLARE.is_match(ip.parse().unwrap()), "{ip}");
+    }
+    for ip in ["8.8.8.8", "172.72.0.1", "2a06:98c8::1"] {
+        assert!(!CLOUDFLARE.is_match(ip.parse().unwrap()), "{ip}");
+    }
+
+    const IPS_V4: &str = "173.245.48.0/20\n103.21.244.0/22\r\n\n  104.16.0.0/13  \n";
+    let mut cidrs = parse_cloudflare_ips(IPS_V4);
+    assert_eq!(cidrs.next().unwrap().unwrap(), http_ip::filter::Cidr::from_text("173.245.48.0/20").unwrap());
+    assert_eq!(cidrs.next().unwrap().unwrap(), http_ip::filter::Cidr::from_text("103.21.244.0/22").unwrap());
+    assert_eq!(cidrs.next().unwrap().unwrap(), http_ip::filter::Cidr::from_text("104.16.0.0/13").unwrap());
+    assert!(cidrs.next().is_none());
+
+    assert!(parse_cloudflare_ips("173.245.48.0/20\ngarbage").any(|cidr| cidr.is_err()));
+
+    let nodes = parse_x_forwarded_for_rev("198.51.100.7, 162.158.1.1");
+    assert_eq!(find_next_ip_after_filter(nodes, &CLOUDFLARE), Some(IpAddr::V4(Ipv4Addr::new(198, 51, 100, 7))));
+}
+
+#[test]
+fn should_match_bogons() {
+    use http_ip::filter::{Filter, BOGONS, NON_PUBLIC};
+
+    for ip in ["0.1.2.3", "192.0.2.1", "198.51.100.1", "203.0.113.1", "198.19.0.1", "224.0.0.1", "239.255.255.255", "255.255.255.255", "240.0.0.1", "::", "2001:db8::1", "3fff::1", "ff02::1", "100::1", "2001:2::1"] {
+        assert!(BOGONS.is_match(ip.parse().unwrap()), "{ip}");
+    }
+
+    for ip in ["10.0.0.1", "172.16.0.1", "192.168.0.1", "100.64.0.1", "127.0.0.1", "169.254.0.1", "::1", "fd00::1", "fe80::1"] {
+        let ip = ip.parse().unwrap();
+        assert!(NON_PUBLIC.is_match(ip), "{ip}");
+        assert!(BOGONS.is_match(ip), "{ip}");
+    }
+
+    for ip in ["8.8.8.8", "1.1.1.1", "198.20.0.1", "223.255.255.255", "2606:4700::1", "2001:4860::8888"] {
+        assert!(!BOGONS.is_match(ip.parse().unwrap()), "{ip}");
+    }
+
+    assert!(BOGONS.cidrs().starts_with(NON_PUBLIC.cidrs()));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_replace_shared_filter() {
+    use http_ip::filter::{Cidr, CidrTrie, Filter, SharedFilter};
+
+    const FORWARDED: &str = "For=203.0.113.195,For=10.0.0.1,For=172.16.0.1";
+
+    let filter = SharedFilter::new(CidrTrie::from_cidrs(&[Cidr::from_text("172.16.0.0/12").unwrap()]));
+    let handle = filter.clone();
+
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &filter).expect("Find ip");
+    assert_eq!(ip, "10.0.0.1".parse::<IpAddr>().unwrap());
+
+    let previous = std::thread::spawn(move || {
+        handle.replace(CidrTrie::from_cidrs(&[Cidr::from_text("172.16.0.0/12").unwrap(), Cidr::from_text("10.0.0.0/8").unwrap()]))
+    }).join().unwrap();
+    assert!(!previous.is_match("10.0.0.1".parse().unwrap()));
+
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &filter).expect("Find ip");
+    assert_eq!(ip, "203.0.113.195".parse::<IpAddr>().unwrap());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_count_filter_matches() {
+    use http_ip::filter::{Cidr, Counted};
+
+    const FORWARDED: &str = "For=203.0.113.195,For=198.51.100.1,For=10.0.0.1";
+
+    let filter = Counted::new(Cidr::from_text("10.0.0.0/8").unwrap());
+    let handle = filter.clone();
+
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &filter).expect("Find ip");
+    assert_eq!(ip, "198.51.100.1".parse::<IpAddr>().unwrap());
+    assert_eq!(handle.matched(), 1);
+    assert_eq!(handle.passed(), 1);
+
+    find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &filter).expect("Find ip");
+    assert_eq!(filter.matched(), 2);
+    assert_eq!(filter.passed(), 2);
+
+    handle.reset();
+    assert_eq!(filter.matched(), 0);
+    assert_eq!(filter.passed(), 0);
+    assert_eq!(format!("{filter:?}"), "Counted { matched: 0, passed: 0 }");
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn should_match_std_collections() {
+    use std::collections::{BTreeSet, HashSet};
+    use http_ip::filter::{Cidr, Filter};
+
+    const FORWARDED: &str = "For=203.0.113.195,For=198.51.100.1,For=10.0.0.1";
+
+    let proxies: Vec<IpAddr> = vec!["198.51.100.1".parse().unwrap(), "10.0.0.1".parse().unwrap()];
+    let btree: BTreeSet<IpAddr> = proxies.iter().copied().collect();
+    let hash: HashSet<IpAddr> = proxies.iter().copied().collect();
+    let cidrs: Vec<Cidr> = vec![Cidr::from_text("198.51.100.0/24").unwrap(), Cidr::from_text("10.0.0.0/8").unwrap()];
+
+    let expected = "203.0.113.195".parse::<IpAddr>().unwrap();
+    assert_eq!(find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &btree), Some(expected));
+    assert_eq!(find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &hash), Some(expected));
+    assert_eq!(find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &cidrs), Some(expected));
+    assert_eq!(find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &proxies), Some(expected));
+
+    assert!(!btree.is_match("10.0.0.2".parse().unwrap()));
+    assert!(!hash.is_match("10.0.0.2".parse().unwrap()));
+    assert!(cidrs.is_match("10.0.0.2".parse().unwrap()));
+}
+
+#[test]
+fn should_check_sorted_ip_set_in_const() {
+    use core::net::Ipv6Addr;
+    use http_ip::filter::{Filter, IpSet};
+
+    const PROXIES: [IpAddr; 4] = [
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+        IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7)),
+        IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)),
+        IpAddr::V6(Ipv6Addr::LOCALHOST),
+    ];
+    const SET: IpSet<&[IpAddr]> = match IpSet::new_sorted_checked(&PROXIES) {
+        Some(set) => set,
+        None => panic!("not sorted"),
+    };
+    const IS_PROXY: bool = SET.contains_const(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7)));
+    const { assert!(IS_PROXY) };
+
+    for ip in PROXIES {
+        assert!(SET.contains_const(ip), "{ip}");
+        assert!(SET.is_match(ip), "{ip}");
+    }
+    for ip in ["10.0.0.2", "0.0.0.0", "255.255.255.255", "::", "::2"] {
+        let ip = ip.parse().unwrap();
+        assert!(!SET.contains_const(ip), "{ip}");
+        assert!(!SET.is_match(ip), "{ip}");
+    }
+
+    let unsorted = [PROXIES[3], PROXIES[0]];
+    assert!(IpSet::new_sorted_checked(&unsorted).is_none());
+    assert!(IpSet::new(unsorted.as_slice()).contains_const(PROXIES[0]));
+    assert!(IpSet::new_sorted_checked(&[]).is_some());
+}
+
+#[test]
+fn should_parse_cidr_lines() {
+    use http_ip::filter::{parse_cidr_lines, Cidr};
+
+    const LIST: &str = "# Load balancers\n10.0.0.0/8\n  192.168.1.1 # health checker\n\n#2001:db8::/32\n2001:db8::/48\r\n";
+
+    let mut cidrs = parse_cidr_lines(LIST);
+    assert_eq!(cidrs.next().unwrap().unwrap(), Cidr::from_text("10.0.0.0/8").unwrap());
+    assert_eq!(cidrs.next().unwrap().unwrap(), Cidr::from_text("192.168.1.1/32").unwrap());
+    assert_eq!(cidrs.next().unwrap().unwrap(), Cidr::from_text("2001:db8::/48").unwrap());
+    assert!(cidrs.next().is_none());
+
+    assert_eq!(parse_cidr_lines("# only comment\n\n").count(), 0);
+    let mut cidrs = parse_cidr_lines("10.0.0.0/8\n10.0.0.0/33");
+    assert!(cidrs.next().unwrap().is_ok());
+    assert!(cidrs.next().unwrap().is_err());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_build_cidr_trie_from_lines() {
+    use http_ip::filter::{CidrTrie, Filter};
+
+    let trie = CidrTrie::from_text_lines("10.0.0.0/8 # internal\n203.0.113.7\n").expect("valid list");
+    assert!(trie.is_match("10.1.1.1".parse().unwrap()));
+    assert!(trie.is_match("203.0.113.7".parse().unwrap()));
+    assert!(!trie.is_match("203.0.113.8".parse().unwrap()));
+
+    let error = CidrTrie::from_text_lines("10.0.0.0/8\n10.0.0.0/33\n").unwrap_err();
+    assert_eq!(error.to_string(), http_ip::filter::Cidr::from_text("10.0.0.0/33").unwrap_err().to_string());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_combine_runtime_filters() {
+    use http_ip::filter::{self, Cidr, DynFilter, Filter};
+
+    let config = ["10.0.0.0/8", "192.168.0.0/16", "2001:db8::/32"];
+    let any = filter::any(config.iter().map(|text| Cidr::from_text(text).unwrap()));
+    assert!(any.is_match("192.168.1.1".parse().unwrap()));
+    assert!(any.is_match("2001:db8::1".parse().unwrap()));
+    assert!(!any.is_match("172.16.0.1".parse().unwrap()));
+    assert!(!filter::any(Vec::<Cidr>::new()).is_match("10.0.0.1".parse().unwrap()));
+
+    let all = filter::all([
+        DynFilter::new(Cidr::from_text("10.0.0.0/8").unwrap()),
+        DynFilter::new(filter::not(Cidr::from_text("10.1.0.0/16").unwrap())),
+    ]);
+    assert!(all.is_match("10.2.0.1".parse().unwrap()));
+    assert!(!all.is_match("10.1.0.1".parse().unwrap()));
+    assert!(!all.is_match("11.0.0.1".parse().unwrap()));
+    assert!(filter::all(Vec::<Cidr>::new()).is_match("10.0.0.1".parse().unwrap()));
+
+    const FORWARDED: &str = "For=203.0.113.195,For=10.2.0.1";
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &all).expect("Find ip");
+    assert_eq!(ip, "203.0.113.195".parse::<IpAddr>().unwrap());
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_build_trusted_proxies() {
+    use http_ip::filter::{self, Cidr, Filter, TrustedProxies, cloud::CLOUDFLARE};
+
+    const FORWARDED: &str = "For=203.0.113.195,For=104.16.0.1,For=198.51.100.1,For=127.0.0.1,For=10.0.0.1";
+
+    let trusted = TrustedProxies::new()
+        .private_networks()
+        .loopback()
+        .preset(CLOUDFLARE)
+        .ip("198.51.100.1".parse().unwrap())
+        .filter(filter::from_fn(|ip: IpAddr| ip.is_multicast()));
+
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &trusted).expect("Find ip");
+    assert_eq!(ip, "203.0.113.195".parse::<IpAddr>().unwrap());
+    assert!(trusted.is_match("224.0.0.1".parse().unwrap()));
+    assert!(!trusted.is_match("198.51.100.2".parse().unwrap()));
+    assert!(!trusted.is_match("169.254.0.1".parse().unwrap()));
+
+    let trusted = TrustedProxies::new().link_local().cidrs([Cidr::from_text("2001:db8::/32").unwrap()]);
+    assert!(trusted.is_match("169.254.0.1".parse().unwrap()));
+    assert!(trusted.is_match("2001:db8::1".parse().unwrap()));
+    assert!(!TrustedProxies::new().is_match("10.0.0.1".parse().unwrap()));
+    assert!(TrustedProxies::new().non_public().is_match("::1".parse().unwrap()));
+}
+
+#[test]
+fn should_convert_cidr_error_into_owned() {
+    use http_ip::filter::{Cidr, CidrParseError};
+
+    fn load(config: &str) -> Result<Cidr, CidrParseError<'static>> {
+        let text = std::string::String::from(config.trim());
+        Cidr::from_text(&text).map_err(CidrParseError::into_owned)
+    }
+
+    assert!(load(" 10.0.0.0/8 ").is_ok());
+
+    let error = load("10.0.0.0/33").unwrap_err();
+    assert_eq!(error, Cidr::from_text("10.0.0.0/33").unwrap_err());
+    let error: Box<dyn std::error::Error + Send + Sync> = Box::new(load("10.0.0.0/8/8").unwrap_err());
+    assert!(!error.to_string().is_empty());
+}
+
+#[test]
+fn should_match_except_filter() {
+    use http_ip::filter::{self, Cidr, Filter};
+
+    const FORWARDED: &str = "For=203.0.113.195,For=10.20.0.5,For=10.0.0.1";
+
+    let trusted = Cidr::from_text("10.0.0.0/8").unwrap().except(Cidr::from_text("10.20.0.0/16").unwrap());
+    assert!(trusted.is_match("10.0.0.1".parse().unwrap()));
+    assert!(!trusted.is_match("10.20.0.5".parse().unwrap()));
+    assert!(!trusted.is_match("11.0.0.1".parse().unwrap()));
+    assert_eq!(trusted.cost_hint(), 4);
+
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &trusted).expect("Find ip");
+    assert_eq!(ip, "10.20.0.5".parse::<IpAddr>().unwrap());
+
+    const TRUSTED: filter::Except<Cidr, IpAddr> = filter::except(
+        match Cidr::from_text("192.168.0.0/16") { Ok(cidr) => cidr, Err(_) => panic!() },
+        IpAddr::V4(Ipv4Addr::new(192, 168, 0, 1)),
+    );
+    assert!(TRUSTED.is_match("192.168.0.2".parse().unwrap()));
+    assert!(!TRUSTED.is_match("192.168.0.1".parse().unwrap()));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_match_asn_filter() {
+    use http_ip::filter::{Cidr, Filter};
+    use http_ip::filter::asn::AsnFilter;
+
+    const CDN_ASN: u32 = 64500;
+    let table = [
+        (Cidr::from_text("198.51.100.0/24").unwrap(), CDN_ASN),
+        (Cidr::from_text("2001:db8::/32").unwrap(), CDN_ASN),
+        (Cidr::from_text("203.0.113.0/24").unwrap(), 64501),
+        (Cidr::from_text("192.0.2.0/24").unwrap(), 64502),
+    ];
+
+    let filter = AsnFilter::from_table(table, &[CDN_ASN, 64502]);
+    assert!(filter.is_match("198.51.100.1".parse().unwrap()));
+    assert!(filter.is_match("2001:db8::1".parse().unwrap()));
+    assert!(filter.is_match("192.0.2.1".parse().unwrap()));
+    assert!(!filter.is_match("203.0.113.1".parse().unwrap()));
+
+    let ip = find_next_ip_after_filter(parse_x_forwarded_for_rev("203.0.113.1, 198.51.100.7"), &filter);
+    assert_eq!(ip, Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 1))));
+    assert!(!AsnFilter::from_table(table, &[]).is_match("198.51.100.1".parse().unwrap()));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_match_bloom_filter() {
+    use core::net::Ipv6Addr;
+    use http_ip::filter::{BloomFilter, Filter};
+
+    let denied: Vec<IpAddr> = (0..10_000u32).map(|idx| IpAddr::V4(Ipv4Addr::from_bits(0x0a00_0000 | (idx * 7)))).collect();
+    let mut filter = BloomFilter::from_ips(denied.iter().copied(), 0.01);
+    for ip in denied.iter() {
+        assert!(filter.is_match(*ip), "{ip}");
+    }
+
+    let false_positives = (0..10_000u32).map(|idx| IpAddr::V4(Ipv4Addr::from_bits(0xc0a8_0000 | idx))).filter(|ip| filter.is_match(*ip)).count();
+    assert!(false_positives < 300, "too many false positives: {false_positives}");
+
+    let ip = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+    filter.insert(ip);
+    assert!(filter.is_match(ip));
+
+    let empty = BloomFilter::new(0, 0.001);
+    assert!(!empty.is_match(ip));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_match_through_pointers() {
+    use std::sync::Arc;
+    use http_ip::filter::{self, Cidr, CidrTrie, Filter};
+
+    fn combine<F: Filter>(filter: F) -> impl Filter {
+        filter.or(IpAddr::V4(Ipv4Addr::LOCALHOST))
+    }
+
+    const FORWARDED: &str = "For=203.0.113.195,For=10.0.0.1,For=127.0.0.1";
+
+    let trie = Arc::new(CidrTrie::from_cidrs(&[Cidr::from_text("10.0.0.0/8").unwrap()]));
+    let shared = trie.clone();
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &combine(shared)).expect("Find ip");
+    assert_eq!(ip, "203.0.113.195".parse::<IpAddr>().unwrap());
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &combine(&*trie)).expect("Find ip");
+    assert_eq!(ip, "203.0.113.195".parse::<IpAddr>().unwrap());
+
+    let boxed: Box<dyn Filter> = Box::new(Cidr::from_text("10.0.0.0/8").unwrap());
+    let ip = find_next_ip_after_filter(parse_forwarded_for_rev(FORWARDED), &combine(boxed)).expect("Find ip");
+    assert_eq!(ip, "203.0.113.195".parse::<IpAddr>().unwrap());
+
+    let dyn_arc: Arc<dyn Filter + Send + Sync> = Arc::new(filter::MatchAll);
+    assert!(dyn_arc.is_match("10.0.0.1".parse().unwrap()));
+    assert_eq!((&&dyn_arc).cost_hint(), 0);
+}
+
+#[test]
+fn should_match_country_filter() {
+    use http_ip::filter::Filter;
+    use http_ip::filter::country::CountryFilter;
+
+    fn lookup(ip: IpAddr) -> Option<[u8; 2]> {
+        match ip {
+            IpAddr::V4(ip) if ip.octets()[0] == 198 => Some(*b"DE"),
+            IpAddr::V4(ip) if ip.octets()[0] == 203 => Some(*b"US"),
+            _ => None,
+        }
+    }
+
+    let filter = CountryFilter::new(lookup, [*b"de", *b"FR"]);
+    assert!(filter.is_match("198.51.100.1".parse().unwrap()));
+    assert!(!filter.is_match("203.0.113.1".parse().unwrap()));
+    assert!(!filter.is_match("10.0.0.1".parse().unwrap()));
+
+    let ip = find_next_ip_after_filter(parse_x_forwarded_for_rev("203.0.113.1, 198.51.100.7"), &filter);
+    assert_eq!(ip, Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 1))));
+}
+
+#[test]
+fn should_decide_policy_verdict() {
+    use http_ip::filter::{Cidr, Filter, Policy, Precedence, Verdict};
+
+    let allow = Cidr::from_text("10.0.0.0/8").unwrap();
+    let deny = Cidr::from_text("10.20.0.0/16").unwrap();
+
+    let policy = Policy::new(allow, deny, Precedence::DenyOverrides);
+    assert_eq!(policy.verdict("10.0.0.1".parse().unwrap()), Verdict::Allow);
+    assert_eq!(policy.verdict("10.20.0.1".parse().unwrap()), Verdict::Deny);
+    assert_eq!(policy.verdict("11.0.0.1".parse().unwrap()), Verdict::Unlisted);
+    assert!(policy.is_match("10.0.0.1".parse().unwrap()));
+    assert!(!policy.is_match("10.20.0.1".parse().unwrap()));
+
+    let policy = Policy::new(deny, allow, Precedence::AllowOverrides);
+    assert_eq!(policy.verdict("10.20.0.1".parse().unwrap()), Verdict::Allow);
+    assert_eq!(policy.verdict("10.0.0.1".parse().unwrap()), Verdict::Deny);
+    assert_eq!(policy.verdict("11.0.0.1".parse().unwrap()), Verdict::Unlisted);
+    assert!(!policy.is_match("11.0.0.1".parse().unwrap()));
+}
+
+#[test]
+fn should_parse_full_forwarded_entry() {
+    use http_ip::forwarded::ForwardedEntry;
+
+    let entry = ForwardedEntry::parse("for=192.0.2.60;proto=https;by=203.0.113.43;host=example.com");
+    assert_eq!(entry.for_, Some(ForwardedNode::Ip("192.0.2.60".parse().unwrap())));
+    assert_eq!(entry.by, Some(ForwardedNode::Ip("203.0.113.43".parse().unwrap())));
+    assert_eq!(entry.proto, Some("https"));
+    assert_eq!(entry.host, Some("example.com"));
+
+    let entries: Vec<ForwardedEntry<'_>> = http_ip::forwarded::parse_hops("for=192.0.2.60;proto=http, for=198.51.100.17;proto=https").collect();
+    assert_eq!(entries[1].for_, Some(ForwardedNode::Ip("198.51.100.17".parse().unwrap())));
+    assert_eq!(entries[1].proto, Some("https"));
+    assert_eq!(ForwardedEntry::parse(""), ForwardedEntry::default());
+}
+
+#[test]
+fn should_parse_forwarded_extensions() {
+    use http_ip::forwarded::ForwardedEntryIter;
+
+    const ENTRY: &str = "for=192.0.2.60;secret=\"abc\"; proto=https;x-vendor=1;invalid";
+
+    let mut entry = ForwardedEntryIter::parse_entry_with_extensions(ENTRY);
+    assert_eq!(entry.next(), Some(ForwardedValue::For(ForwardedNode::Ip("192.0.2.60".parse().unwrap()))));
+    assert_eq!(entry.next(), Some(ForwardedValue::Extension("secret", "\"abc\"")));
+    assert_eq!(entry.next(), Some(ForwardedValue::Protocol("https")));
+    assert_eq!(entry.next(), Some(ForwardedValue::Extension("x-vendor", "1")));
+    assert!(entry.next().is_none());
+
+    let mut entry = ForwardedEntryIter::parse_entry(ENTRY);
+    assert_eq!(entry.next(), Some(ForwardedValue::For(ForwardedNode::Ip("192.0.2.60".parse().unwrap()))));
+    assert_eq!(entry.next(), Some(ForwardedValue::Protocol("https")));
+    assert!(entry.next().is_none());
+}
+
+#[test]
+fn should_respect_quoted_strings_in_forwarded() {
+    const HEADER: &str = "for=\"[2001:db8::1]:80\";host=\"a,b;c\", for=198.51.100.17;secret=\"x\\\",y\"";
+
+    let nodes: Vec<_> = parse_forwarded_for(HEADER).collect();
+    assert_eq!(nodes, [
+        ForwardedNode::Ip("2001:db8::1".parse().unwrap()),
+        ForwardedNode::Ip("198.51.100.17".parse().unwrap()),
+    ]);
+    let nodes_rev: Vec<_> = parse_forwarded_for_rev(HEADER).collect();
+    assert_eq!(nodes_rev, [nodes[1], nodes[0]]);
+
+    let mut entries = parse_forwarded(HEADER);
+    let mut entry = entries.next().unwrap();
+    assert_eq!(entry.next(), Some(ForwardedValue::For(nodes[0])));
+    assert_eq!(entry.next(), Some(ForwardedValue::Host("\"a,b;c\"")));
+    assert!(entry.next().is_none());
+    let mut entry = entries.next().unwrap();
+    assert_eq!(entry.next(), Some(ForwardedValue::For(nodes[1])));
+    assert!(entry.next().is_none());
+    assert!(entries.next().is_none());
+
+    let mut entries = parse_forwarded_rev(HEADER);
+    assert_eq!(entries.next().unwrap().next(), Some(ForwardedValue::For(nodes[1])));
+    assert_eq!(entries.next().unwrap().next(), Some(ForwardedValue::For(nodes[0])));
+    assert!(entries.next().is_none());
+}
+
+#[test]
+fn should_split_quoted_forwarded_same_in_both_directions() {
+    const HEADERS: [&str; 8] = [
+        "for=\"a,b\";by=\"c;d\", for=198.51.100.17",
+        "for=\"a\\\\\",for=192.0.2.1;host=\"x\\\\\\\";y\"",
+        "for=\"192.0.2.1,for=198.51.100.17",
+        "for=192.0.2.1;host=\"a,b\", for=\"unterminated, for=198.51.100.17",
+        "for=\"\\\"\\\", for=192.0.2.1, for=\"",
+        "for=\"\"\",\"\";for=192.0.2.1",
+        ",;\"\",;",
+        "",
+    ];
+
+    for header in HEADERS {
+        let mut nodes: Vec<_> = parse_forwarded_for(header).collect();
+        nodes.reverse();
+        assert_eq!(parse_forwarded_for_rev(header).collect::<Vec<_>>(), nodes, "{header}");
+
+        let entries = parse_forwarded(header).map(|entry| entry.collect::<Vec<_>>()).collect::<Vec<_>>();
+        let mut entries_rev = parse_forwarded_rev(header).map(|entry| entry.collect::<Vec<_>>()).collect::<Vec<_>>();
+        entries_rev.reverse();
+        assert_eq!(entries, entries_rev, "{header}");
+    }
+
+    let nodes: Vec<_> = parse_forwarded_for(HEADERS[1]).collect();
+    assert_eq!(nodes, [ForwardedNode::Name("a\\\\"), ForwardedNode::Ip("192.0.2.1".parse().unwrap())]);
+    let nodes: Vec<_> = parse_forwarded_for(HEADERS[3]).collect();
+    assert_eq!(nodes, [
+        ForwardedNode::Ip("192.0.2.1".parse().unwrap()),
+        ForwardedNode::Name("unterminated"),
+        ForwardedNode::Ip("198.51.100.17".parse().unwrap()),
+    ]);
+
+    let mut header = String::new();
+    for idx in 0..2000u16 {
+        header.push_str(&format!("for=\"192.0.{}.{}\";host=\"a,b;c\\\"d\", ", idx / 256, idx % 256));
+    }
+    assert_eq!(parse_forwarded_for_rev(&header).count(), 2000);
+    assert_eq!(parse_forwarded_for_rev(&header).next(), Some(ForwardedNode::Ip("192.0.7.207".parse().unwrap())));
+}
+
+#[test]
+fn should_split_escaped_quotes_in_forwarded_same_in_both_directions() {
+    fn assert_same_split(header: &str) {
+        let mut nodes: Vec<_> = parse_forwarded_for(header).collect();
+        nodes.reverse();
+        assert_eq!(parse_forwarded_for_rev(header).collect::<Vec<_>>(), nodes, "{header}");
+
+        let entries = parse_forwarded(header).map(|entry| entry.collect::<Vec<_>>()).collect::<Vec<_>>();
+        let mut entries_rev = parse_forwarded_rev(header).map(|entry| entry.collect::<Vec<_>>()).collect::<Vec<_>>();
+        entries_rev.reverse();
+        assert_eq!(entries, entries_rev, "{header}");
+    }
+
+    const HEADER: &str = "for=9.9.9.9,for=x\\\"y\"";
+    assert_same_split(HEADER);
+    assert_same_split("for=9.9.9.9,for=x\\\"y\";for=1.1.1.1");
+    assert_eq!(parse_forwarded_for_rev(HEADER).collect::<Vec<_>>(), [
+        ForwardedNode::Name("x\\\"y"),
+        ForwardedNode::Ip("9.9.9.9".parse().unwrap()),
+    ]);
+
+    const PARTS: [&str; 7] = ["for=1.1.1.1", "for=\"", "\\", "\"", ",", ";", "x"];
+    let mut header = String::new();
+    for len in 1..=5u32 {
+        for mut idx in 0..PARTS.len().pow(len) {
+            header.clear();
+            for _ in 0..len {
+                header.push_str(PARTS[idx % PARTS.len()]);
+                idx /= PARTS.len();
+            }
+            assert_same_split(&header);
+        }
+    }
+}
+
+#[test]
+fn should_unescape_quoted_forwarded_node() {
+    assert_eq!(ForwardedNode::parse_node("\"192.0.2\\.1\""), ForwardedNode::Ip("192.0.2.1".parse().unwrap()));
+    assert_eq!(ForwardedNode::parse_node_with_port("\"[2001:db8::\\1]:8080\""), (ForwardedNode::Ip("2001:db8::1".parse().unwrap()), Some("8080")));
+    assert_eq!(ForwardedNode::parse_node_with_port("\"[2001:db8::1]:80\\80\""), (ForwardedNode::Ip("2001:db8::1".parse().unwrap()), None));
+    assert_eq!(ForwardedNode::parse_node("\"_hid\\den\""), ForwardedNode::Name("_hid\\den"));
+
+    let nodes: Vec<_> = parse_forwarded_for("for=\"192.0.2\\.1\", for=\"_a\\\"b,c\"").collect();
+    assert_eq!(nodes, [ForwardedNode::Ip("192.0.2.1".parse().unwrap()), ForwardedNode::Name("_a\\\"b,c")]);
+}
+
+#[test]
+fn should_query_cost_hint_once_in_combinators() {
+    use core::cell::Cell;
+    use http_ip::filter::{self, Filter};
+
+    struct Counting<'a>(&'a Cell<usize>, IpAddr);
+
+    impl Filter for Counting<'_> {
+        fn is_match(&self, ip: IpAddr) -> bool {
+            self.1 == ip
+        }
+
+        fn cost_hint(&self) -> u32 {
+            self.0.set(self.0.get() + 1);
+            8
+        }
+    }
+
+    let ip = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+    let calls = Cell::new(0);
+    let filter = Counting(&calls, ip).or(Counting(&calls, ip)).or(Counting(&calls, ip)).and(Counting(&calls, ip));
+    for _ in 0..10 {
+        assert!(filter.is_match(ip));
+        assert!(!filter.is_match(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2))));
+    }
+    assert_eq!(filter.cost_hint(), 32);
+    assert_eq!(calls.get(), 4);
+
+    let filter = filter::or(Counting(&calls, ip), ip);
+    assert_eq!(filter.cost_hint(), 9);
+    assert!(filter.is_match(ip));
+    assert_eq!(calls.get(), 5);
+}
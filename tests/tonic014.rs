@@ -16,7 +16,7 @@ fn should_format_header_map() {
     headers.append(X_FORWARDED_FOR, "203.0.113.195,2001:db8:85a3:8d3:1319:8a2e:370:7348,198.51.100.178".parse().unwrap());
 
     let display = headers.get_header_value_fmt(FORWARDED).to_string();
-    assert_eq!(display, "By=\"[2001:db8:cafe::17]:4711\",For=127.0.0.1 ,For=unknown,For=_hidden");
+    assert_eq!(display, "By=\"[2001:db8:cafe::17]:4711\",For=127.0.0.1, For=unknown,For=_hidden");
 }
 
 #[test]
@@ -184,3 +184,571 @@ fn should_extract_filtered_by_cidr_from_header_map_with_or() {
     let result = headers.extract_filtered_forwarded_ip(&filter).expect("to get ip");
     assert_eq!(result, expected_ip);
 }
+
+#[test]
+fn should_extract_x_client_ip_from_header_map() {
+    let mut headers = MetadataMap::new();
+
+    headers.append("x-client-ip", "203.0.113.9".parse().unwrap());
+
+    let ip = headers.extract_x_client_ip().expect("to have IP");
+    let expected_ip: IpAddr = "203.0.113.9".parse().unwrap();
+    assert_eq!(expected_ip, ip);
+
+    headers.append("x-forwarded", " 10.0.0.1 ".parse().unwrap());
+    let ip = headers.extract_ip_from("x-forwarded").expect("to have IP");
+    let expected_ip: IpAddr = "10.0.0.1".parse().unwrap();
+    assert_eq!(expected_ip, ip);
+
+    let result = headers.extract_ip_from("x-real-ip");
+    assert!(result.is_none(), "Unexpected IP={:?}", result);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_explain_filtered_forwarded_ip() {
+    use http_ip::extract::{HeaderSource, ResolutionStep};
+    use http_ip::forwarded::ForwardedNode;
+
+    let mut headers = MetadataMap::new();
+
+    headers.append(FORWARDED, "For=203.0.113.1,For=10.0.0.1".parse().unwrap());
+    headers.append(X_FORWARDED_FOR, "198.51.100.178".parse().unwrap());
+
+    let filtered_ip: IpAddr = "10.0.0.1".parse().unwrap();
+    let expected_ip: IpAddr = "203.0.113.1".parse().unwrap();
+    let resolution = headers.explain_filtered_forwarded_ip(&filtered_ip);
+    assert_eq!(resolution.ip, Some(expected_ip));
+    assert_eq!(resolution.ip, headers.extract_filtered_forwarded_ip(&filtered_ip));
+    assert_eq!(resolution.steps, [
+        ResolutionStep {
+            source: HeaderSource::Forwarded,
+            node: ForwardedNode::Ip(filtered_ip),
+            is_match: true,
+        },
+        ResolutionStep {
+            source: HeaderSource::Forwarded,
+            node: ForwardedNode::Ip(expected_ip),
+            is_match: false,
+        },
+    ]);
+
+    let mut headers = MetadataMap::new();
+    headers.append(X_FORWARDED_FOR, "_hidden,10.0.0.1".parse().unwrap());
+    let resolution = headers.explain_filtered_forwarded_ip(&filtered_ip);
+    assert_eq!(resolution.ip, None);
+    assert_eq!(resolution.steps, [
+        ResolutionStep {
+            source: HeaderSource::XForwardedFor,
+            node: ForwardedNode::Ip(filtered_ip),
+            is_match: true,
+        },
+        ResolutionStep {
+            source: HeaderSource::XForwardedFor,
+            node: ForwardedNode::Name("_hidden"),
+            is_match: false,
+        },
+    ]);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_collect_all_ips_with_source() {
+    use http_ip::extract::HeaderSource;
+
+    let mut headers = MetadataMap::new();
+
+    headers.append(FORWARDED, "By=\"[2001:db8:cafe::17]:4711\",For=127.0.0.1".parse().unwrap());
+    headers.append(FORWARDED, "For=unknown,For=192.168.0.1".parse().unwrap());
+    headers.append(X_FORWARDED_FOR, "203.0.113.195,198.51.100.178".parse().unwrap());
+    headers.append("x-real-ip", "10.0.0.1".parse().unwrap());
+
+    let ips = headers.collect_all_ips_with_source();
+    assert_eq!(ips, [
+        ("127.0.0.1".parse().unwrap(), HeaderSource::Forwarded),
+        ("192.168.0.1".parse().unwrap(), HeaderSource::Forwarded),
+        ("203.0.113.195".parse().unwrap(), HeaderSource::XForwardedFor),
+        ("198.51.100.178".parse().unwrap(), HeaderSource::XForwardedFor),
+        ("10.0.0.1".parse().unwrap(), HeaderSource::XRealIp),
+    ]);
+}
+
+#[test]
+fn should_extract_filtered_ip_with_header_priority() {
+    use http_ip::extract::{ExtractConfig, HeaderSource};
+
+    let mut headers = MetadataMap::new();
+
+    headers.append(FORWARDED, "For=192.168.0.1,For=10.0.0.1".parse().unwrap());
+    headers.append(X_FORWARDED_FOR, "203.0.113.195,10.0.0.1".parse().unwrap());
+
+    let filtered_ip: IpAddr = "10.0.0.1".parse().unwrap();
+    let forwarded_ip: IpAddr = "192.168.0.1".parse().unwrap();
+    let x_forwarded_ip: IpAddr = "203.0.113.195".parse().unwrap();
+
+    let result = headers.extract_filtered_forwarded_ip_with(&filtered_ip, &ExtractConfig::default()).expect("to get ip");
+    assert_eq!(result, forwarded_ip);
+    assert_eq!(Some(result), headers.extract_filtered_forwarded_ip(&filtered_ip));
+
+    let config = ExtractConfig {
+        header_priority: &[HeaderSource::XForwardedFor, HeaderSource::Forwarded],
+    };
+    let result = headers.extract_filtered_forwarded_ip_with(&filtered_ip, &config).expect("to get ip");
+    assert_eq!(result, x_forwarded_ip);
+
+    let config = ExtractConfig {
+        header_priority: &[HeaderSource::XRealIp],
+    };
+    let result = headers.extract_filtered_forwarded_ip_with(&filtered_ip, &config);
+    assert!(result.is_none(), "Unexpected IP={:?}", result);
+
+    let config = ExtractConfig {
+        header_priority: &[HeaderSource::XRealIp, HeaderSource::XForwardedFor],
+    };
+    let result = headers.extract_filtered_forwarded_ip_with(&filtered_ip, &config).expect("to get ip");
+    assert_eq!(result, x_forwarded_ip);
+}
+
+#[test]
+fn should_extract_nearest_proxy_ip_from_header_map() {
+    let mut headers = MetadataMap::new();
+
+    headers.append(FORWARDED, "For=192.168.0.1,For=10.0.0.1".parse().unwrap());
+    headers.append(FORWARDED, "By=10.0.0.2".parse().unwrap());
+    headers.append(X_FORWARDED_FOR, "203.0.113.195,198.51.100.178".parse().unwrap());
+
+    let filtered_ip: IpAddr = "10.0.0.1".parse().unwrap();
+    let expected_ip: IpAddr = "192.168.0.1".parse().unwrap();
+    let result = headers.extract_filtered_forwarded_ip(&filtered_ip).expect("to get ip");
+    assert_eq!(result, expected_ip);
+    let result = headers.extract_nearest_proxy_ip().expect("to get ip");
+    assert_eq!(result, filtered_ip);
+
+    let mut headers = MetadataMap::new();
+    headers.append(X_FORWARDED_FOR, "203.0.113.195,198.51.100.178".parse().unwrap());
+    let expected_ip: IpAddr = "198.51.100.178".parse().unwrap();
+    let result = headers.extract_nearest_proxy_ip().expect("to get ip");
+    assert_eq!(result, expected_ip);
+}
+
+#[cfg(feature = "smallvec")]
+#[test]
+fn should_extract_chain_smallvec() {
+    let mut headers = MetadataMap::new();
+
+    headers.append(FORWARDED, "For=203.0.113.195;proto=https,For=10.0.0.1".parse().unwrap());
+    headers.append(X_FORWARDED_FOR, "198.51.100.178".parse().unwrap());
+
+    let chain = headers.extract_chain_smallvec();
+    let expected: [IpAddr; 2] = ["203.0.113.195".parse().unwrap(), "10.0.0.1".parse().unwrap()];
+    assert_eq!(chain.as_slice(), expected);
+    assert!(!chain.spilled());
+
+    headers.append(FORWARDED, "For=10.0.0.2,For=_hidden,For=10.0.0.3,For=10.0.0.4,For=10.0.0.5,For=10.0.0.6".parse().unwrap());
+    let chain = headers.extract_chain_smallvec();
+    assert_eq!(chain.len(), 7);
+    assert_eq!(chain[0], expected[0]);
+    assert_eq!(chain[6], "10.0.0.6".parse::<IpAddr>().unwrap());
+    assert!(chain.spilled());
+
+    let mut headers = MetadataMap::new();
+    headers.append(X_FORWARDED_FOR, "203.0.113.195,198.51.100.178".parse().unwrap());
+    let chain = headers.extract_chain_smallvec();
+    let expected: [IpAddr; 2] = ["203.0.113.195".parse().unwrap(), "198.51.100.178".parse().unwrap()];
+    assert_eq!(chain.as_slice(), expected);
+}
+
+#[test]
+fn should_extract_first_trusted_ip_from_header_map() {
+    let mut headers = MetadataMap::new();
+
+    headers.append(FORWARDED, "For=203.0.113.195,For=10.0.0.7".parse().unwrap());
+    headers.append(FORWARDED, "For=198.51.100.178".parse().unwrap());
+
+    let trusted = Cidr::from_text("10.0.0.0/24").expect("to parse");
+    let expected_ip: IpAddr = "10.0.0.7".parse().unwrap();
+    let result = headers.extract_first_trusted_forwarded_ip(&trusted).expect("to get ip");
+    assert_eq!(result, expected_ip);
+
+    let trusted = Cidr::from_text("192.168.0.0/24").expect("to parse");
+    let result = headers.extract_first_trusted_forwarded_ip(&trusted);
+    assert!(result.is_none(), "Unexpected IP={:?}", result);
+
+    let mut headers = MetadataMap::new();
+    headers.append(X_FORWARDED_FOR, "10.0.0.5,_hidden,203.0.113.195".parse().unwrap());
+    let trusted = Cidr::from_text("10.0.0.0/24").expect("to parse");
+    let result = headers.extract_first_trusted_forwarded_ip(&trusted);
+    assert!(result.is_none(), "Unexpected IP={:?}", result);
+}
+
+#[test]
+fn should_extract_client_ip_after_trusted_hops() {
+    let mut headers = MetadataMap::new();
+
+    headers.append(FORWARDED, "For=203.0.113.195,For=10.0.0.2".parse().unwrap());
+    headers.append(FORWARDED, "For=10.0.0.1".parse().unwrap());
+
+    let trusted = Cidr::from_text("10.0.0.0/24").expect("to parse");
+    let expected_ip: IpAddr = "203.0.113.195".parse().unwrap();
+
+    //exactly enough
+    let result = headers.extract_client_ip_trusted_hops(&trusted, 2).expect("to get ip");
+    assert_eq!(result, expected_ip);
+    //more than enough
+    let result = headers.extract_client_ip_trusted_hops(&trusted, 1).expect("to get ip");
+    assert_eq!(result, expected_ip);
+    let result = headers.extract_client_ip_trusted_hops(&trusted, 0).expect("to get ip");
+    assert_eq!(result, expected_ip);
+    //too few
+    let result = headers.extract_client_ip_trusted_hops(&trusted, 3);
+    assert!(result.is_none(), "Unexpected IP={:?}", result);
+}
+
+#[test]
+fn should_extract_filtered_by_dyn_filter_from_header_map() {
+    let mut headers = MetadataMap::new();
+
+    headers.append(FORWARDED, "For=192.168.0.1,For=10.0.0.1".parse().unwrap());
+
+    let filters: [&dyn filter::Filter; 2] = [&(), &Cidr::from_text("10.0.0.0/24").expect("to parse")];
+    let result = headers.extract_filtered_forwarded_ip(filters[0]).expect("to get ip");
+    assert_eq!(result, "10.0.0.1".parse::<IpAddr>().unwrap());
+    let result = headers.extract_filtered_forwarded_ip(filters[1]).expect("to get ip");
+    assert_eq!(result, "192.168.0.1".parse::<IpAddr>().unwrap());
+}
+
+#[test]
+fn should_check_forwarded_xff_consistency() {
+    let filter = Cidr::from_text("10.0.0.0/24").expect("to parse");
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "For=203.0.113.195,For=10.0.0.1".parse().unwrap());
+    assert_eq!(headers.forwarded_xff_consistent(&filter), None);
+
+    headers.append(X_FORWARDED_FOR, "203.0.113.195,10.0.0.2,10.0.0.1".parse().unwrap());
+    assert_eq!(headers.forwarded_xff_consistent(&filter), Some(true));
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "For=203.0.113.195,For=10.0.0.1".parse().unwrap());
+    headers.append(X_FORWARDED_FOR, "198.51.100.178,10.0.0.1".parse().unwrap());
+    assert_eq!(headers.forwarded_xff_consistent(&filter), Some(false));
+
+    let mut headers = MetadataMap::new();
+    headers.append(X_FORWARDED_FOR, "198.51.100.178,10.0.0.1".parse().unwrap());
+    assert_eq!(headers.forwarded_xff_consistent(&filter), None);
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "For=_hidden,For=10.0.0.1".parse().unwrap());
+    headers.append(X_FORWARDED_FOR, "unknown,10.0.0.1".parse().unwrap());
+    assert_eq!(headers.forwarded_xff_consistent(&filter), None);
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "For=203.0.113.195,For=10.0.0.1".parse().unwrap());
+    headers.append(X_FORWARDED_FOR, "unknown,10.0.0.1".parse().unwrap());
+    assert_eq!(headers.forwarded_xff_consistent(&filter), None);
+}
+
+#[test]
+fn should_iterate_forwarded_for_nodes_across_lines() {
+    use http_ip::forwarded::ForwardedNode;
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "For=203.0.113.195;proto=https,for=_hidden".parse().unwrap());
+    headers.append(FORWARDED, "for=10.0.0.2,for=unknown;by=10.0.0.1".parse().unwrap());
+
+    let expected = [
+        ForwardedNode::Ip("203.0.113.195".parse().unwrap()),
+        ForwardedNode::Name("_hidden"),
+        ForwardedNode::Ip("10.0.0.2".parse().unwrap()),
+        ForwardedNode::Unknown,
+    ];
+    assert!(headers.forwarded_for_nodes().eq(expected.iter().copied()));
+    assert!(headers.forwarded_for_nodes_rev().eq(expected.iter().rev().copied()));
+
+    let headers = MetadataMap::new();
+    assert_eq!(headers.forwarded_for_nodes().next(), None);
+    assert_eq!(headers.forwarded_for_nodes_rev().next(), None);
+}
+
+#[test]
+fn should_extract_effective_scheme() {
+    let mut headers = MetadataMap::new();
+    assert_eq!(headers.extract_effective_scheme(), None);
+
+    headers.append("x-forwarded-proto", "https, http".parse().unwrap());
+    assert_eq!(headers.extract_effective_scheme(), Some("https"));
+
+    headers.append(FORWARDED, "for=192.0.2.43;proto=\"http\",for=10.0.0.1;proto=https".parse().unwrap());
+    assert_eq!(headers.extract_effective_scheme(), Some("http"));
+
+    let mut headers = MetadataMap::new();
+    headers.append("x-forwarded-proto", "ftp, https".parse().unwrap());
+    assert_eq!(headers.extract_effective_scheme(), None);
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "for=192.0.2.43;proto=wss".parse().unwrap());
+    headers.append("x-forwarded-proto", "https".parse().unwrap());
+    assert_eq!(headers.extract_effective_scheme(), None);
+}
+
+#[test]
+fn should_write_header_value_fmt_without_allocation() {
+    struct FixedBuf {
+        buf: [u8; 64],
+        len: usize,
+    }
+
+    impl core::fmt::Write for FixedBuf {
+        fn write_str(&mut self, text: &str) -> core::fmt::Result {
+            let end = self.len + text.len();
+            if end > self.buf.len() {
+                return Err(core::fmt::Error);
+            }
+            self.buf[self.len..end].copy_from_slice(text.as_bytes());
+            self.len = end;
+            Ok(())
+        }
+    }
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "For=127.0.0.1".parse().unwrap());
+    headers.append(FORWARDED, "For=unknown,For=_hidden".parse().unwrap());
+
+    let value_fmt = headers.get_header_value_fmt(FORWARDED);
+    let mut out = FixedBuf {
+        buf: [0; 64],
+        len: 0,
+    };
+    value_fmt.write_to(&mut out).expect("to write");
+    let written = core::str::from_utf8(&out.buf[..out.len]).unwrap();
+    assert_eq!(written, "For=127.0.0.1, For=unknown,For=_hidden");
+    assert_eq!(written, value_fmt.to_string());
+}
+
+#[test]
+fn should_extract_filtered_leftmost_forwarded_ip() {
+    let filter = filter::or(Cidr::from_text("10.0.0.0/8").unwrap(), Cidr::from_text("192.168.0.0/16").unwrap());
+    let expected: IpAddr = "203.0.113.195".parse().unwrap();
+
+    let mut headers = MetadataMap::new();
+    assert_eq!(headers.extract_filtered_leftmost_forwarded_ip(&filter), None);
+
+    headers.append(X_FORWARDED_FOR, "192.168.1.1,10.0.0.5,203.0.113.195,198.51.100.178".parse().unwrap());
+    assert_eq!(headers.extract_filtered_leftmost_forwarded_ip(&filter), Some(expected));
+
+    headers.append(FORWARDED, "for=10.1.1.1".parse().unwrap());
+    headers.append(FORWARDED, "for=203.0.113.195,for=10.0.0.1".parse().unwrap());
+    assert_eq!(headers.extract_filtered_leftmost_forwarded_ip(&filter), Some(expected));
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "for=10.1.1.1,for=_hidden,for=203.0.113.195".parse().unwrap());
+    assert_eq!(headers.extract_filtered_leftmost_forwarded_ip(&filter), None);
+}
+
+#[test]
+fn should_extract_ip_from_binary_metadata() {
+    use http_ip::tonic014::tonic::metadata::MetadataValue;
+
+    let expected: IpAddr = "2001:db8::1".parse().unwrap();
+    let mut headers = MetadataMap::new();
+    assert_eq!(headers.extract_ip_from_binary("x-client-ip-bin"), None);
+
+    headers.append_bin("x-client-ip-bin", MetadataValue::from_bytes(b"2001:db8::1"));
+    assert_eq!(headers.extract_ip_from_binary("x-client-ip-bin"), Some(expected));
+    //Binary metadata is invisible to ascii lookups
+    assert_eq!(headers.extract_ip_from("x-client-ip-bin"), None);
+    //Key without -bin suffix cannot be binary
+    assert_eq!(headers.extract_ip_from_binary("x-client-ip"), None);
+
+    headers.append_bin("x-client-ip-bin", MetadataValue::from_bytes(&[0xff, 0xfe, 0x00]));
+    assert_eq!(headers.extract_ip_from_binary("x-client-ip-bin"), None);
+
+    headers.append_bin("x-client-ip-bin", MetadataValue::from_bytes(b"garbage"));
+    assert_eq!(headers.extract_ip_from_binary("x-client-ip-bin"), None);
+}
+
+#[test]
+fn should_extract_client_ip_leftmost_trusted_across_lines() {
+    let expected: IpAddr = "203.0.113.195".parse().unwrap();
+
+    let mut headers = MetadataMap::new();
+    assert_eq!(headers.extract_client_ip_leftmost_trusted(), None);
+
+    headers.append(X_FORWARDED_FOR, "invalid".parse().unwrap());
+    headers.append(X_FORWARDED_FOR, "203.0.113.195,10.0.0.1".parse().unwrap());
+    assert_eq!(headers.extract_client_ip_leftmost_trusted(), Some(expected));
+
+    headers.append(FORWARDED, "for=unknown,for=_hidden".parse().unwrap());
+    headers.append(FORWARDED, "for=\"[2001:db8::1]\",for=203.0.113.195".parse().unwrap());
+    assert_eq!(headers.extract_client_ip_leftmost_trusted(), Some("2001:db8::1".parse().unwrap()));
+    //Plain leftmost stops at first line
+    assert_eq!(headers.extract_leftmost_forwarded_ip(), None);
+}
+
+#[test]
+fn should_extract_client_ip_with_depth() {
+    let filter = filter::or(Cidr::from_text("10.0.0.0/24").unwrap(), Cidr::from_text("192.168.0.0/16").unwrap());
+    let expected: IpAddr = "203.0.113.195".parse().unwrap();
+
+    let mut headers = MetadataMap::new();
+    assert_eq!(headers.extract_client_ip_with_depth(&filter), None);
+
+    headers.append(X_FORWARDED_FOR, "203.0.113.195".parse().unwrap());
+    assert_eq!(headers.extract_client_ip_with_depth(&filter), Some((expected, 0)));
+
+    headers.append(X_FORWARDED_FOR, "192.168.1.1,10.0.0.1".parse().unwrap());
+    assert_eq!(headers.extract_client_ip_with_depth(&filter), Some((expected, 2)));
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "for=_hidden,for=10.0.0.1".parse().unwrap());
+    assert_eq!(headers.extract_client_ip_with_depth(&filter), None);
+}
+
+#[test]
+fn should_extract_filtered_ip_from_custom_header() {
+    let filter = Cidr::from_text("10.0.0.0/24").unwrap();
+    let expected: IpAddr = "203.0.113.195".parse().unwrap();
+
+    let mut headers = MetadataMap::new();
+    headers.append("x-cluster-client-ip", "203.0.113.195, 10.0.0.1".parse().unwrap());
+
+    assert_eq!(headers.extract_filtered_ip_from("x-cluster-client-ip", &filter), Some(expected));
+    assert_eq!(headers.extract_filtered_ip_from("x-missing", &filter), None);
+}
+
+#[test]
+fn should_extract_first_public_forwarded_ip() {
+    let mut headers = MetadataMap::new();
+    assert_eq!(headers.extract_first_public_forwarded_ip(), None);
+
+    headers.append(X_FORWARDED_FOR, "10.0.0.1, 203.0.113.5, 198.51.100.7".parse().unwrap());
+    assert_eq!(headers.extract_first_public_forwarded_ip(), Some("203.0.113.5".parse().unwrap()));
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "for=_hidden,for=127.0.0.1,for=\"[fd00::1]\",for=100.64.0.1".parse().unwrap());
+    assert_eq!(headers.extract_first_public_forwarded_ip(), None);
+    headers.append(FORWARDED, "for=\"[::ffff:192.168.0.1]\",for=\"[2001:db8::1]\"".parse().unwrap());
+    assert_eq!(headers.extract_first_public_forwarded_ip(), Some("2001:db8::1".parse().unwrap()));
+}
+
+#[test]
+fn should_extract_last_public_forwarded_ip() {
+    let mut headers = MetadataMap::new();
+    assert_eq!(headers.extract_last_public_forwarded_ip(), None);
+
+    headers.append(X_FORWARDED_FOR, "203.0.113.5, 198.51.100.7, 100.64.1.1, 10.0.0.1".parse().unwrap());
+    assert_eq!(headers.extract_last_public_forwarded_ip(), Some("198.51.100.7".parse().unwrap()));
+    assert_eq!(headers.extract_first_public_forwarded_ip(), Some("203.0.113.5".parse().unwrap()));
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "for=10.0.0.1,for=_hidden,for=192.168.0.1".parse().unwrap());
+    assert_eq!(headers.extract_last_public_forwarded_ip(), None);
+}
+
+#[test]
+fn should_extract_rightmost_by_ip() {
+    let mut headers = MetadataMap::new();
+    assert_eq!(headers.extract_rightmost_by_ip(), None);
+
+    headers.append(FORWARDED, "for=192.0.2.43;by=10.0.0.1".parse().unwrap());
+    assert_eq!(headers.extract_rightmost_by_ip(), Some("10.0.0.1".parse().unwrap()));
+
+    headers.append(FORWARDED, "for=198.51.100.17;by=\"[2001:db8::17]:4711\",for=10.0.0.2".parse().unwrap());
+    assert_eq!(headers.extract_rightmost_by_ip(), Some("2001:db8::17".parse().unwrap()));
+
+    headers.append(FORWARDED, "for=10.0.0.3;by=_proxy".parse().unwrap());
+    assert_eq!(headers.extract_rightmost_by_ip(), None);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn should_collect_distinct_chain_ips() {
+    let mut headers = MetadataMap::new();
+    assert!(headers.distinct_chain_ips().is_empty());
+
+    headers.append(FORWARDED, "for=203.0.113.195,for=_hidden,for=10.0.0.1".parse().unwrap());
+    headers.append(FORWARDED, "for=\"[2001:db8::1]\",for=10.0.0.1".parse().unwrap());
+    headers.append(X_FORWARDED_FOR, "203.0.113.195, 198.51.100.178, 10.0.0.1".parse().unwrap());
+
+    let expected: [IpAddr; 4] = ["10.0.0.1".parse().unwrap(), "198.51.100.178".parse().unwrap(), "203.0.113.195".parse().unwrap(), "2001:db8::1".parse().unwrap()];
+    assert_eq!(headers.distinct_chain_ips(), expected);
+}
+
+#[test]
+fn should_extract_filtered_forwarded_ip_bounded() {
+    let filter = Cidr::from_text("10.0.0.0/24").unwrap();
+    let expected: IpAddr = "203.0.113.195".parse().unwrap();
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "for=203.0.113.195,for=10.0.0.2".parse().unwrap());
+    headers.append(FORWARDED, "for=10.0.0.1".parse().unwrap());
+
+    assert_eq!(headers.extract_filtered_forwarded_ip_bounded(&filter, 3), Some(expected));
+    assert_eq!(headers.extract_filtered_forwarded_ip_bounded(&filter, 10), Some(expected));
+    assert_eq!(headers.extract_filtered_forwarded_ip_bounded(&filter, 2), None);
+    assert_eq!(headers.extract_filtered_forwarded_ip_bounded(&filter, 0), None);
+
+    let mut headers = MetadataMap::new();
+    headers.append(X_FORWARDED_FOR, "1.1.1.1,1.1.1.2,1.1.1.3,1.1.1.4,1.1.1.5,203.0.113.195".parse().unwrap());
+    assert_eq!(headers.extract_filtered_forwarded_ip_bounded(&filter, 5), None);
+    assert_eq!(headers.extract_filtered_forwarded_ip_bounded(&filter, 6), Some(expected));
+}
+
+#[test]
+fn should_extract_filtered_forwarded_ip_prefer_v4() {
+    let filter = Cidr::from_text("10.0.0.0/24").unwrap();
+
+    let mut headers = MetadataMap::new();
+    assert_eq!(headers.extract_filtered_forwarded_ip_prefer_v4(&filter), None);
+
+    headers.append(FORWARDED, "for=\"[2001:db8::1]\";for=203.0.113.195;proto=https,for=10.0.0.1".parse().unwrap());
+    assert_eq!(headers.extract_filtered_forwarded_ip_prefer_v4(&filter), Some("203.0.113.195".parse().unwrap()));
+    assert_eq!(headers.extract_filtered_forwarded_ip(&filter), Some("203.0.113.195".parse().unwrap()));
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "for=203.0.113.195;for=\"[2001:db8::1]\",for=10.0.0.1".parse().unwrap());
+    assert_eq!(headers.extract_filtered_forwarded_ip_prefer_v4(&filter), Some("203.0.113.195".parse().unwrap()));
+    assert_eq!(headers.extract_filtered_forwarded_ip(&filter), Some("2001:db8::1".parse().unwrap()));
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "for=198.51.100.178,for=\"[2001:db8::1]\";for=10.0.0.2".parse().unwrap());
+    assert_eq!(headers.extract_filtered_forwarded_ip_prefer_v4(&filter), Some("2001:db8::1".parse().unwrap()));
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "for=198.51.100.178,for=_hidden;for=10.0.0.2".parse().unwrap());
+    assert_eq!(headers.extract_filtered_forwarded_ip_prefer_v4(&filter), None);
+
+    let mut headers = MetadataMap::new();
+    headers.append(X_FORWARDED_FOR, "198.51.100.178, 2001:db8::1, 10.0.0.1".parse().unwrap());
+    assert_eq!(headers.extract_filtered_forwarded_ip_prefer_v4(&filter), Some("2001:db8::1".parse().unwrap()));
+}
+
+#[test]
+fn should_resolve_client_ip_by_classifier() {
+    use http_ip::extract::HopKind;
+    use http_ip::filter::Filter;
+
+    let trusted = Cidr::from_text("10.0.0.0/24").unwrap();
+    let banned: IpAddr = "198.51.100.178".parse().unwrap();
+    let classify = |ip: IpAddr| if trusted.is_match(ip) {
+        HopKind::Trusted
+    } else if ip == banned {
+        HopKind::Reject
+    } else {
+        HopKind::Client
+    };
+
+    let mut headers = MetadataMap::new();
+    assert_eq!(headers.resolve_client_ip_by(classify), None);
+
+    headers.append(X_FORWARDED_FOR, "198.51.100.178, 203.0.113.195, 10.0.0.2, 10.0.0.1".parse().unwrap());
+    assert_eq!(headers.resolve_client_ip_by(classify), Some("203.0.113.195".parse().unwrap()));
+
+    let mut headers = MetadataMap::new();
+    headers.append(X_FORWARDED_FOR, "203.0.113.195, 198.51.100.178, 10.0.0.1".parse().unwrap());
+    assert_eq!(headers.resolve_client_ip_by(classify), None);
+
+    let mut headers = MetadataMap::new();
+    headers.append(FORWARDED, "for=203.0.113.195,for=_hidden,for=10.0.0.1".parse().unwrap());
+    assert_eq!(headers.resolve_client_ip_by(classify), None);
+}